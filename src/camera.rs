@@ -1,25 +1,60 @@
+use std::f64::consts::{PI, TAU};
+
 use rand::{distr::Uniform, prelude::*};
 
 use crate::{
     graphics::Ray,
-    math::{Point, Vec3},
+    math::{Keyframes, Point, Transform, Vec3},
 };
 
+/// How a [`Camera`] maps points on the image to the directions it sees.
+#[derive(Clone, Copy, Default, PartialEq, Eq)]
+pub enum Projection {
+    /// A pinhole or thin lens, which keeps straight lines straight.
+    #[default]
+    Perspective,
+    /// Parallel rays from a view as large as the perspective view at the focus distance, without
+    /// perspective, like an architectural elevation.
+    Orthographic,
+    /// A fisheye lens whose image radius grows in proportion to the angle from the viewing
+    /// direction, covering the vertical field of view across the image's height.
+    EquidistantFisheye,
+    /// A fisheye lens whose image area grows in proportion to the solid angle it covers, covering
+    /// the vertical field of view across the image's height.
+    EquisolidFisheye,
+    /// Every direction, with longitude across the image and latitude up it, for 360° panoramas at
+    /// an aspect ratio of 2.
+    Equirectangular,
+    /// Every direction, as the faces of a cube around the camera, for reflection probes at an
+    /// aspect ratio of 1.5.
+    ///
+    /// The faces are 90° views laid out in two rows: right, left and up; then down, back and
+    /// forwards. This is the order of the OpenGL cube map faces in the camera's frame, with x to
+    /// the right, y up and z backwards.
+    CubeMap,
+}
+
 #[non_exhaustive]
 pub struct Camera {
+    projection: Projection,
     origin: Point,
     lower_left_corner: Point,
     horizontal: Vec3,
     vertical: Vec3,
     u: Vec3,
     v: Vec3,
+    w: Vec3,
+    focus_dist: f64,
     lens_radius: f64,
+    v_fov: f64,
+    aspect_ratio: f64,
     dist: Uniform<f64>,
 }
 
 impl Camera {
     #[allow(clippy::too_many_arguments)]
     pub fn new(
+        projection: Projection,
         from: Point,
         to: Point,
         v_up: Vec3,
@@ -45,31 +80,251 @@ impl Camera {
         let lower_left_corner = origin - (horizontal / 2.0) - (vertical / 2.0) - focus_dist * w;
 
         Self {
+            projection,
             origin,
             lower_left_corner,
             horizontal,
             vertical,
             u,
             v,
+            w,
+            focus_dist,
             lens_radius: aperture / 2.0,
+            v_fov: theta,
+            aspect_ratio,
             dist: Uniform::new_inclusive(t_start, t_end).unwrap(),
         }
     }
 
-    pub fn ray(&self, s: f64, t: f64, rng: &mut ThreadRng) -> Ray {
-        let rd = self.lens_radius * Vec3::random_in_unit_disc(rng);
-        let offset = self.u * rd.x + self.v * rd.y;
-
-        Ray::new(
-            self.origin + offset,
-            // self.lower_left_corner + (u * self.horizontal) + (v * self.vertical) - self.origin
-            //   - offset
-            self.horizontal.mul_add(
-                s,
-                self.vertical
-                    .mul_add(t, self.lower_left_corner - self.origin - offset),
+    pub fn ray(&self, s: f64, t: f64, rng: &mut dyn RngCore) -> Ray {
+        match self.projection {
+            Projection::Perspective => {
+                let rd = self.lens_radius * Vec3::random_in_unit_disc(rng);
+                let offset = self.u * rd.x + self.v * rd.y;
+
+                Ray::new(
+                    self.origin + offset,
+                    // self.lower_left_corner + (u * self.horizontal) + (v * self.vertical)
+                    //   - self.origin - offset
+                    self.horizontal.mul_add(
+                        s,
+                        self.vertical
+                            .mul_add(t, self.lower_left_corner - self.origin - offset),
+                    ),
+                    self.time(rng),
+                )
+            }
+            Projection::Orthographic => Ray::new(
+                self.horizontal
+                    .mul_add(s - 0.5, self.vertical.mul_add(t - 0.5, self.origin)),
+                -self.w,
+                self.time(rng),
             ),
-            self.dist.sample(rng),
+            _ => {
+                let d = self.panoramic(s, t);
+                let direction = self.u.mul_add(d.x, self.v.mul_add(d.y, d.z * self.w));
+
+                Ray::new(self.origin, direction, self.time(rng))
+            }
+        }
+    }
+
+    /// The unit direction through viewport coordinates `(s, t)` of the fisheye and panoramic
+    /// projections, in the camera's frame with x to the right, y up and z backwards.
+    fn panoramic(&self, s: f64, t: f64) -> Vec3 {
+        // The direction at an angle `theta` from the viewing direction, towards `(x, y)` on the
+        // image.
+        let fisheye = |x: f64, y: f64, theta: f64| {
+            let r = x.hypot(y);
+            if r <= 0.0 {
+                return Vec3::newi(0, 0, -1);
+            }
+            let (sin, cos) = theta.sin_cos();
+            Vec3::newf(sin * x / r, sin * y / r, -cos)
+        };
+        // Coordinates from the center of the image, which is 1 high.
+        let (x, y) = ((s - 0.5) * self.aspect_ratio, t - 0.5);
+
+        match self.projection {
+            Projection::EquidistantFisheye => fisheye(x, y, x.hypot(y) * self.v_fov),
+            Projection::EquisolidFisheye => {
+                // The image radius is 2 f sin(theta / 2), which is 0.5 at half the field of view.
+                let f = 0.25 / (self.v_fov / 4.0).sin();
+                let theta = 2.0 * (x.hypot(y) / (2.0 * f)).min(1.0).asin();
+                fisheye(x, y, theta)
+            }
+            Projection::Equirectangular => {
+                let (sin_phi, cos_phi) = ((s - 0.5) * TAU).sin_cos();
+                let (sin_lambda, cos_lambda) = ((t - 0.5) * PI).sin_cos();
+                Vec3::newf(cos_lambda * sin_phi, sin_lambda, -cos_lambda * cos_phi)
+            }
+            _ => {
+                let column = (3.0 * s).floor().clamp(0.0, 2.0);
+                let row = (2.0 * t).floor().clamp(0.0, 1.0);
+                let a = 2.0f64.mul_add(3.0f64.mul_add(s, -column), -1.0);
+                let b = 2.0f64.mul_add(2.0f64.mul_add(t, -row), -1.0);
+                // The direction to the center of each face, and to its right and top edges.
+                let (forward, right, up) = match (row as u8, column as u8) {
+                    (1, 0) => (
+                        Vec3::newi(1, 0, 0),
+                        Vec3::newi(0, 0, 1),
+                        Vec3::newi(0, 1, 0),
+                    ),
+                    (1, 1) => (
+                        Vec3::newi(-1, 0, 0),
+                        Vec3::newi(0, 0, -1),
+                        Vec3::newi(0, 1, 0),
+                    ),
+                    (1, _) => (
+                        Vec3::newi(0, 1, 0),
+                        Vec3::newi(1, 0, 0),
+                        Vec3::newi(0, 0, 1),
+                    ),
+                    (_, 0) => (
+                        Vec3::newi(0, -1, 0),
+                        Vec3::newi(1, 0, 0),
+                        Vec3::newi(0, 0, -1),
+                    ),
+                    (_, 1) => (
+                        Vec3::newi(0, 0, 1),
+                        Vec3::newi(-1, 0, 0),
+                        Vec3::newi(0, 1, 0),
+                    ),
+                    _ => (
+                        Vec3::newi(0, 0, -1),
+                        Vec3::newi(1, 0, 0),
+                        Vec3::newi(0, 1, 0),
+                    ),
+                };
+                right.mul_add(a, up.mul_add(b, forward)).unit()
+            }
+        }
+    }
+
+    /// A random time while the shutter is open.
+    pub fn time(&self, rng: &mut dyn RngCore) -> f64 {
+        self.dist.sample(rng)
+    }
+
+    /// The area of the lens, or 1 for a pinhole camera whose lens is a single point.
+    pub fn lens_area(&self) -> f64 {
+        if self.lens_radius > 0.0 {
+            PI * self.lens_radius * self.lens_radius
+        } else {
+            1.0
+        }
+    }
+
+    /// A point sampled uniformly on the lens.
+    pub fn sample_lens(&self, rng: &mut dyn RngCore) -> Point {
+        let rd = self.lens_radius * Vec3::random_in_unit_disc(rng);
+        self.origin + self.u * rd.x + self.v * rd.y
+    }
+
+    /// The cosine of the angle between `direction` and the viewing direction.
+    pub fn cos_theta(&self, direction: Vec3) -> f64 {
+        -direction.unit().dot(self.w)
+    }
+
+    /// The area of the viewport at a distance of 1 from the lens.
+    ///
+    /// For orthographic cameras, the area of the view, which is the same at any distance; and for
+    /// fisheye and panoramic cameras, the product of the angles the image spans across and up.
+    pub fn viewport_area(&self) -> f64 {
+        match self.projection {
+            Projection::Perspective => {
+                (self.horizontal.len() / self.focus_dist) * (self.vertical.len() / self.focus_dist)
+            }
+            Projection::Orthographic => self.horizontal.len() * self.vertical.len(),
+            Projection::EquidistantFisheye | Projection::EquisolidFisheye => {
+                self.aspect_ratio * self.v_fov * self.v_fov
+            }
+            Projection::Equirectangular => TAU * PI,
+            Projection::CubeMap => 1.5 * PI * PI,
+        }
+    }
+
+    /// The viewport coordinates, as passed to [`Camera::ray`], of the ray from the point `lens` on
+    /// the lens through `p`; or `None` if `p` is behind the lens.
+    pub fn project(&self, lens: Point, p: Point) -> Option<(f64, f64)> {
+        let d = p - lens;
+        let depth = -d.dot(self.w);
+        if depth <= 0.0 {
+            return None;
+        }
+
+        // Every ray through a point on the plane of focus maps to the same viewport coordinates.
+        let focus = d.mul_add(self.focus_dist / depth, lens) - self.lower_left_corner;
+        Some((
+            focus.dot(self.horizontal) / self.horizontal.len_squared(),
+            focus.dot(self.vertical) / self.vertical.len_squared(),
+        ))
+    }
+}
+
+/// Camera placement and lens settings, with the placement animated by [`Keyframes`] that transform
+/// the look-from point, look-at point and up vector.
+#[non_exhaustive]
+pub struct CameraRig {
+    pub from: Point,
+    pub to: Point,
+    pub v_up: Vec3,
+    pub v_fov: f64,
+    pub aperture: f64,
+    pub focus_dist: f64,
+    pub keyframes: Keyframes,
+    pub projection: Projection,
+}
+
+impl CameraRig {
+    pub fn new(
+        from: Point,
+        to: Point,
+        v_up: Vec3,
+        v_fov: f64,
+        aperture: f64,
+        focus_dist: f64,
+    ) -> Self {
+        Self {
+            from,
+            to,
+            v_up,
+            v_fov,
+            aperture,
+            focus_dist,
+            keyframes: Keyframes::fixed(Transform::IDENTITY),
+            projection: Projection::Perspective,
+        }
+    }
+
+    #[must_use]
+    pub const fn with_projection(mut self, projection: Projection) -> Self {
+        self.projection = projection;
+        self
+    }
+
+    #[must_use]
+    pub fn with_keyframes(mut self, keyframes: Keyframes) -> Self {
+        self.keyframes = keyframes;
+        self
+    }
+
+    /// A [`Camera`] placed by the keyframes at `t_start`, with its shutter open from `t_start` to
+    /// `t_end`.
+    pub fn camera(&self, aspect_ratio: f64, t_start: f64, t_end: f64) -> Camera {
+        let transform = self.keyframes.at(t_start);
+
+        Camera::new(
+            self.projection,
+            transform.point(self.from),
+            transform.point(self.to),
+            transform.vector(self.v_up),
+            self.v_fov,
+            aspect_ratio,
+            self.aperture,
+            self.focus_dist,
+            t_start,
+            t_end,
         )
     }
 }
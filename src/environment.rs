@@ -0,0 +1,504 @@
+//! Light from infinitely far away.
+
+use std::{
+    f64::consts::{FRAC_PI_2, PI},
+    path::Path,
+};
+
+use image::ImageResult;
+use rand::prelude::*;
+
+use crate::{
+    graphics::Ray,
+    light::DirectionalLight,
+    math::{Quat, Rgb, Vec3},
+    object::Sphere,
+    rng::CLOSED_OPEN_01,
+    spectrum,
+};
+
+/// Trait for the light arriving from infinitely far away, seen by rays that escape the scene.
+pub trait Environment: Send + Sync {
+    /// The color seen along a ray that hits nothing.
+    fn color(&self, r: &Ray) -> Rgb;
+
+    /// Whether no light arrives from the environment, so it needs no sampling.
+    fn is_black(&self) -> bool {
+        false
+    }
+
+    /// A unit direction towards the environment, sampled in rough proportion to the light arriving
+    /// from it, and the probability density of sampling it. By default directions are uniformly
+    /// distributed over the unit sphere.
+    fn sample(&self, rng: &mut dyn RngCore) -> Option<(Vec3, f64)> {
+        (!self.is_black()).then(|| (Vec3::random_unit_vec(rng), (4.0 * PI).recip()))
+    }
+
+    /// The probability density, with respect to solid angle, of [`Environment::sample`] sampling
+    /// `direction`.
+    fn pdf(&self, _direction: Vec3) -> f64 {
+        if self.is_black() {
+            0.0
+        } else {
+            (4.0 * PI).recip()
+        }
+    }
+}
+
+/// A vertical gradient from one color below the horizon to another straight up.
+pub struct Gradient {
+    bottom: Rgb,
+    top: Rgb,
+}
+
+impl Gradient {
+    pub const fn new(bottom: Rgb, top: Rgb) -> Self {
+        Self { bottom, top }
+    }
+
+    /// A white to light blue sky.
+    pub const fn sky() -> Self {
+        Self::new(Rgb::ONE, Rgb::newf(0.5, 0.7, 1.0))
+    }
+}
+
+impl Environment for Gradient {
+    fn color(&self, r: &Ray) -> Rgb {
+        let unit_direction = r.direction.unit();
+        let t = 0.5 * (unit_direction.y + 1.0);
+        // (1.0 - t) * self.bottom + t * self.top
+        self.bottom.mul_add(1.0 - t, t * self.top)
+    }
+}
+
+/// A single color in every direction.
+pub struct Constant {
+    color: Rgb,
+}
+
+impl Constant {
+    pub const fn new(color: Rgb) -> Self {
+        Self { color }
+    }
+}
+
+impl Environment for Constant {
+    fn color(&self, _r: &Ray) -> Rgb {
+        self.color
+    }
+
+    fn is_black(&self) -> bool {
+        self.color.near_zero()
+    }
+}
+
+/// An equirectangular image of the light arriving from each direction, such as a Radiance HDR
+/// image, importance sampled by luminance.
+///
+/// The image wraps around the vertical axis in the same way as an image texture on a [`Sphere`]
+/// seen from inside, with the top row straight up.
+pub struct EnvironmentMap {
+    width: usize,
+    height: usize,
+    pixels: Vec<Rgb>,
+    /// Rotation around the vertical axis.
+    rotation: Quat,
+    intensity: f64,
+    distribution: Distribution2D,
+}
+
+impl EnvironmentMap {
+    /// Load an environment map from an image file.
+    ///
+    /// # Errors
+    ///
+    /// If the image can't be opened or decoded.
+    pub fn open(path: impl AsRef<Path>) -> ImageResult<Self> {
+        let image = image::open(path)?.into_rgb32f();
+        let (width, height) = (image.width() as usize, image.height() as usize);
+        let pixels: Vec<Rgb> = image
+            .pixels()
+            .map(|&image::Rgb([r, g, b])| Rgb::newf(f64::from(r), f64::from(g), f64::from(b)))
+            .collect();
+
+        // Rows near the poles cover less of the sphere, so they are sampled less often.
+        let weights = (0..height)
+            .map(|row| {
+                let sin_theta = (PI * (row as f64 + 0.5) / height as f64).sin();
+                pixels[row * width..(row + 1) * width]
+                    .iter()
+                    .map(|pixel| pixel.luminance().max(0.0) * sin_theta)
+                    .collect()
+            })
+            .collect();
+
+        Ok(Self {
+            width,
+            height,
+            pixels,
+            rotation: Quat::IDENTITY,
+            intensity: 1.0,
+            distribution: Distribution2D::new(weights),
+        })
+    }
+
+    /// Rotate the environment `degrees` counterclockwise around the vertical axis, seen from
+    /// above.
+    #[must_use]
+    pub fn with_rotation(mut self, degrees: f64) -> Self {
+        self.rotation = Quat::from_axis_angle(Vec3::newi(0, 1, 0), degrees);
+        self
+    }
+
+    /// Scale the light arriving from every direction by `intensity`.
+    #[must_use]
+    pub const fn with_intensity(mut self, intensity: f64) -> Self {
+        self.intensity = intensity;
+        self
+    }
+
+    /// Image coordinates in [0, 1) of a unit direction in the image's frame, from the left and the
+    /// top of the image, and the sine of its angle from straight up.
+    fn uv(direction: Vec3) -> (f64, f64, f64) {
+        let (u, v) = Sphere::uv(&direction);
+        let sin_theta = direction.y.mul_add(-direction.y, 1.0).max(0.0).sqrt();
+
+        (u, 1.0 - v, sin_theta)
+    }
+
+    /// The unit direction in the image's frame of image coordinates `(u, v)`, the inverse of
+    /// [`EnvironmentMap::uv`], and the sine of its angle from straight up.
+    fn direction(u: f64, v: f64) -> (Vec3, f64) {
+        let phi = 2.0f64.mul_add(PI * u, -PI);
+        let (sin_theta, cos_theta) = (PI * v).sin_cos();
+        let (sin_phi, cos_phi) = phi.sin_cos();
+
+        (
+            Vec3::newf(sin_theta * cos_phi, cos_theta, -sin_theta * sin_phi),
+            sin_theta,
+        )
+    }
+}
+
+impl Environment for EnvironmentMap {
+    fn color(&self, r: &Ray) -> Rgb {
+        let (u, v, _) = Self::uv(self.rotation.conjugate().rotate(r.direction.unit()));
+        let x = ((u * self.width as f64) as usize).min(self.width - 1);
+        let y = ((v * self.height as f64) as usize).min(self.height - 1);
+
+        self.intensity * self.pixels[y * self.width + x]
+    }
+
+    fn is_black(&self) -> bool {
+        self.intensity <= 0.0 || self.distribution.integral() <= 0.0
+    }
+
+    fn sample(&self, rng: &mut dyn RngCore) -> Option<(Vec3, f64)> {
+        if self.is_black() {
+            return None;
+        }
+
+        let ((u, v), pdf) = self
+            .distribution
+            .sample(CLOSED_OPEN_01.sample(rng), CLOSED_OPEN_01.sample(rng));
+        let (direction, sin_theta) = Self::direction(u, v);
+        if pdf <= 0.0 || sin_theta <= 0.0 {
+            return None;
+        }
+
+        // The image covers 2π by π radians, squeezed by sin θ towards the poles.
+        Some((
+            self.rotation.rotate(direction),
+            pdf / (2.0 * PI * PI * sin_theta),
+        ))
+    }
+
+    fn pdf(&self, direction: Vec3) -> f64 {
+        if self.is_black() {
+            return 0.0;
+        }
+
+        let (u, v, sin_theta) = Self::uv(self.rotation.conjugate().rotate(direction.unit()));
+        if sin_theta <= 0.0 {
+            return 0.0;
+        }
+
+        self.distribution.pdf(u, v) / (2.0 * PI * PI * sin_theta)
+    }
+}
+
+/// A piecewise constant distribution over [0, 1).
+struct Distribution1D {
+    weights: Vec<f64>,
+    /// The cumulative distribution at the start of each piece, and 1 at the end.
+    cdf: Vec<f64>,
+    /// The average weight.
+    integral: f64,
+}
+
+impl Distribution1D {
+    fn new(weights: Vec<f64>) -> Self {
+        let n = weights.len() as f64;
+        let mut cdf = Vec::with_capacity(weights.len() + 1);
+        cdf.push(0.0);
+        for weight in &weights {
+            cdf.push(cdf[cdf.len() - 1] + weight / n);
+        }
+        let integral = cdf[cdf.len() - 1];
+
+        // Without any weight, every piece is equally likely.
+        for (i, c) in cdf.iter_mut().enumerate() {
+            *c = if integral > 0.0 {
+                *c / integral
+            } else {
+                i as f64 / n
+            };
+        }
+
+        Self {
+            weights,
+            cdf,
+            integral,
+        }
+    }
+
+    /// A value sampled from the distribution by inverting the cumulative distribution at `u`, its
+    /// probability density, and the index of its piece.
+    fn sample(&self, u: f64) -> (f64, f64, usize) {
+        let i = (self.cdf.partition_point(|&c| c <= u) - 1).min(self.weights.len() - 1);
+        let width = self.cdf[i + 1] - self.cdf[i];
+        let offset = if width > 0.0 {
+            (u - self.cdf[i]) / width
+        } else {
+            0.0
+        };
+
+        (
+            (i as f64 + offset) / self.weights.len() as f64,
+            self.pdf(i),
+            i,
+        )
+    }
+
+    /// The probability density of values in piece `i`.
+    fn pdf(&self, i: usize) -> f64 {
+        if self.integral > 0.0 {
+            self.weights[i] / self.integral
+        } else {
+            1.0
+        }
+    }
+
+    /// The piece containing `x`.
+    fn index(&self, x: f64) -> usize {
+        ((x * self.weights.len() as f64) as usize).min(self.weights.len() - 1)
+    }
+}
+
+/// A piecewise constant distribution over [0, 1)², sampled one row at a time.
+struct Distribution2D {
+    rows: Vec<Distribution1D>,
+    marginal: Distribution1D,
+}
+
+impl Distribution2D {
+    /// A distribution with the weight of each column of each row.
+    fn new(weights: Vec<Vec<f64>>) -> Self {
+        let rows: Vec<_> = weights.into_iter().map(Distribution1D::new).collect();
+        let marginal = Distribution1D::new(rows.iter().map(|row| row.integral).collect());
+
+        Self { rows, marginal }
+    }
+
+    const fn integral(&self) -> f64 {
+        self.marginal.integral
+    }
+
+    /// A point `(u, v)` sampled with `u1` and `u2`, and its probability density.
+    fn sample(&self, u1: f64, u2: f64) -> ((f64, f64), f64) {
+        let (v, pdf_v, row) = self.marginal.sample(u2);
+        let (u, pdf_u, _) = self.rows[row].sample(u1);
+
+        ((u, v), pdf_u * pdf_v)
+    }
+
+    fn pdf(&self, u: f64, v: f64) -> f64 {
+        let row = self.marginal.index(v);
+        let column = self.rows[row].index(u);
+
+        self.marginal.pdf(row) * self.rows[row].pdf(column)
+    }
+}
+
+/// The daylight sky model of Preetham, Shirley and Smits.
+///
+/// The sky is lit by a sun at a given elevation and azimuth through an atmosphere of a given
+/// turbidity. The sun itself is left out of the sky, and lights the scene as the
+/// [`DirectionalLight`] given by [`PreethamSky::sun`].
+///
+/// The sky below the horizon is the same as at the horizon.
+pub struct PreethamSky {
+    /// The unit direction towards the sun.
+    sun: Vec3,
+    /// The angle of the sun from straight up, in radians.
+    sun_zenith: f64,
+    turbidity: f64,
+    /// The luminance and chromaticity of the sky straight up.
+    zenith: [f64; 3],
+    /// The Perez distribution coefficients of the luminance and chromaticity.
+    coefficients: [[f64; 5]; 3],
+    intensity: f64,
+}
+
+impl PreethamSky {
+    /// Scale from luminance in thousands of candelas per square metre to the renderer's units,
+    /// which leaves a white surface facing a high sun a little short of white.
+    const EXPOSURE: f64 = 0.03;
+
+    /// A sky lit by a sun `elevation` degrees above the horizon, clamped to between 0 and 90, and
+    /// `azimuth` degrees counterclockwise from +x seen from above. The turbidity is the haziness
+    /// of the atmosphere, from 2 for a clear sky to about 10 for a hazy one.
+    pub fn new(elevation: f64, azimuth: f64, turbidity: f64) -> Self {
+        let elevation = elevation.clamp(0.0, 90.0).to_radians();
+        let azimuth = azimuth.to_radians();
+        let t = turbidity.max(1.0);
+        let theta = FRAC_PI_2 - elevation;
+
+        let chi = (4.0 / 9.0 - t / 120.0) * 2.0f64.mul_add(-theta, PI);
+        let luminance = 4.0453f64
+            .mul_add(t, -4.9710)
+            .mul_add(chi.tan(), (-0.2155f64).mul_add(t, 2.4192));
+        let chromaticity = |coefficients: [[f64; 4]; 3]| {
+            let [t2, t1, t0] = coefficients
+                .map(|[a, b, c, d]| a.mul_add(theta, b).mul_add(theta, c).mul_add(theta, d));
+            (t * t).mul_add(t2, t.mul_add(t1, t0))
+        };
+        let x = chromaticity([
+            [0.00166, -0.00375, 0.00209, 0.0],
+            [-0.02903, 0.06377, -0.03202, 0.00394],
+            [0.11693, -0.21196, 0.06052, 0.25886],
+        ]);
+        let y = chromaticity([
+            [0.00275, -0.00610, 0.00317, 0.0],
+            [-0.04214, 0.08970, -0.04153, 0.00516],
+            [0.15346, -0.26756, 0.06670, 0.26688],
+        ]);
+
+        let coefficients = [
+            [
+                (0.1787, -1.4630),
+                (-0.3554, 0.4275),
+                (-0.0227, 5.3251),
+                (0.1206, -2.5771),
+                (-0.0670, 0.3703),
+            ],
+            [
+                (-0.0193, -0.2592),
+                (-0.0665, 0.0008),
+                (-0.0004, 0.2125),
+                (-0.0641, -0.8989),
+                (-0.0033, 0.0452),
+            ],
+            [
+                (-0.0167, -0.2608),
+                (-0.0950, 0.0092),
+                (-0.0079, 0.2102),
+                (-0.0441, -1.6537),
+                (-0.0109, 0.0529),
+            ],
+        ]
+        .map(|row| row.map(|(slope, intercept): (f64, f64)| slope.mul_add(t, intercept)));
+
+        let (sin_elevation, cos_elevation) = elevation.sin_cos();
+        let (sin_azimuth, cos_azimuth) = azimuth.sin_cos();
+
+        Self {
+            sun: Vec3::newf(
+                cos_elevation * cos_azimuth,
+                sin_elevation,
+                -cos_elevation * sin_azimuth,
+            ),
+            sun_zenith: theta,
+            turbidity: t,
+            zenith: [luminance.max(0.0), x, y],
+            coefficients,
+            intensity: 1.0,
+        }
+    }
+
+    /// Scale the light from the sky and the sun by `intensity`.
+    #[must_use]
+    pub const fn with_intensity(mut self, intensity: f64) -> Self {
+        self.intensity = intensity;
+        self
+    }
+
+    /// The sun as a light the size of the sun's disc, dimmed and reddened by the air it passes
+    /// through.
+    pub fn sun(&self) -> DirectionalLight {
+        // Kasten and Young's relative air mass, and the transmittance of molecules and aerosols
+        // at wavelengths representative of each color channel, in micrometres.
+        let zenith_degrees = self.sun_zenith.to_degrees();
+        let air_mass = 0.15f64
+            .mul_add(
+                (93.885 - zenith_degrees).powf(-1.253),
+                self.sun_zenith.cos(),
+            )
+            .recip();
+        let beta = 0.04608f64.mul_add(self.turbidity, -0.04586);
+        let transmittance = |wavelength: f64| {
+            let rayleigh = 0.008_735 * wavelength.powf(-4.08);
+            let aerosol = beta * wavelength.powf(-1.3);
+            (-(rayleigh + aerosol) * air_mass).exp()
+        };
+
+        // The illuminance of sunlight outside the atmosphere, in thousands of lux.
+        let irradiance = 128.0
+            * Self::EXPOSURE
+            * self.intensity
+            * Rgb::newf(
+                transmittance(0.68),
+                transmittance(0.55),
+                transmittance(0.44),
+            );
+
+        DirectionalLight::new(-self.sun, irradiance).with_angular_diameter(0.53)
+    }
+
+    /// The Perez distribution of sky light at an angle `theta` from straight up and `gamma` from
+    /// the sun.
+    fn perez([a, b, c, d, e]: [f64; 5], theta: f64, gamma: f64) -> f64 {
+        let cos_gamma = gamma.cos();
+        a.mul_add((b / theta.cos()).exp(), 1.0)
+            * e.mul_add(cos_gamma * cos_gamma, c.mul_add((d * gamma).exp(), 1.0))
+    }
+}
+
+impl Environment for PreethamSky {
+    fn color(&self, r: &Ray) -> Rgb {
+        let direction = r.direction.unit();
+        // Directions at and below the horizon see the sky just above it.
+        let theta = direction.y.clamp(0.01, 1.0).acos();
+        let gamma = direction.dot(self.sun).clamp(-1.0, 1.0).acos();
+
+        let [luminance, x, y] = [0, 1, 2].map(|i| {
+            self.zenith[i] * Self::perez(self.coefficients[i], theta, gamma)
+                / Self::perez(self.coefficients[i], 0.0, self.sun_zenith)
+        });
+        if y <= 0.0 {
+            return Rgb::ZERO;
+        }
+
+        // xyY to XYZ to linear sRGB.
+        let luminance = Self::EXPOSURE * self.intensity * luminance;
+        let big_x = x / y * luminance;
+        let big_z = (1.0 - x - y) / y * luminance;
+        let rgb = spectrum::xyz_to_rgb(Vec3::newf(big_x, luminance, big_z));
+
+        Rgb::newf(rgb.x.max(0.0), rgb.y.max(0.0), rgb.z.max(0.0))
+    }
+
+    fn is_black(&self) -> bool {
+        self.intensity <= 0.0
+    }
+}
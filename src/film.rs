@@ -0,0 +1,223 @@
+//! Image sampling.
+
+use std::{
+    ops::AddAssign,
+    sync::atomic::{AtomicU64, Ordering},
+};
+
+use indicatif::ProgressBar;
+use rand::prelude::*;
+use rayon::prelude::*;
+
+use crate::{camera::Camera, graphics::Ray, math::Rgb, rng::CLOSED_OPEN_01};
+
+/// The dimensions and sample count of a rendered image.
+#[non_exhaustive]
+#[derive(Clone, Copy)]
+pub struct Film {
+    pub image_width: u32,
+    pub image_height: u32,
+    pub samples_per_pixel: u32,
+}
+
+impl Film {
+    pub const fn new(image_width: u32, image_height: u32, samples_per_pixel: u32) -> Self {
+        Self {
+            image_width,
+            image_height,
+            samples_per_pixel,
+        }
+    }
+
+    /// The sum of `samples_per_pixel` colors returned by `sample` for camera rays through each
+    /// pixel, from the top row of the image to the bottom.
+    pub fn render<T, F>(&self, camera: &Camera, bar: &ProgressBar, sample: F) -> Vec<T>
+    where
+        T: AddAssign + Default + Send,
+        F: Fn(&Ray, &mut dyn RngCore) -> T + Sync,
+    {
+        let Self {
+            image_width,
+            image_height,
+            samples_per_pixel,
+        } = *self;
+
+        (0..image_height * image_width)
+            .into_par_iter()
+            .map(|i| {
+                if i % image_width == 0 {
+                    bar.inc(1);
+                }
+
+                let mut rng = rand::rng();
+                let mut pixel = T::default();
+
+                for _ in 0..samples_per_pixel {
+                    let r = self.ray(camera, i as usize, &mut rng);
+                    pixel += sample(&r, &mut rng);
+                }
+
+                pixel
+            })
+            .collect::<Vec<T>>() // TODO: avoid intermediate allocation
+        // may require a parallel to sequential adapter
+        // https://github.com/rayon-rs/rayon/issues/210
+    }
+
+    /// A camera ray through a random point in the pixel at `index` into the rendered pixels.
+    pub fn ray(&self, camera: &Camera, index: usize, rng: &mut dyn RngCore) -> Ray {
+        let (x, y) = (
+            index as u32 % self.image_width,
+            index as u32 / self.image_width,
+        );
+        let y = self.image_height - y - 1;
+
+        let u = (f64::from(x) + CLOSED_OPEN_01.sample(rng)) / f64::from(self.image_width - 1);
+        let v = (f64::from(y) + CLOSED_OPEN_01.sample(rng)) / f64::from(self.image_height - 1);
+
+        camera.ray(u, v, rng)
+    }
+
+    /// A camera ray through a random point anywhere in the image, and the index into the rendered
+    /// pixels of the pixel it passes through.
+    pub fn sample_ray(&self, camera: &Camera, rng: &mut dyn RngCore) -> (usize, Ray) {
+        let x = CLOSED_OPEN_01.sample(rng) * f64::from(self.image_width);
+        let y = CLOSED_OPEN_01.sample(rng) * f64::from(self.image_height);
+        let (column, row) = (
+            (x as u32).min(self.image_width - 1),
+            (y as u32).min(self.image_height - 1),
+        );
+        let index = (self.image_height - row - 1) * self.image_width + column;
+
+        let u = x / f64::from(self.image_width - 1);
+        let v = y / f64::from(self.image_height - 1);
+
+        (index as usize, camera.ray(u, v, rng))
+    }
+
+    /// The number of pixels in the image.
+    pub const fn pixel_count(&self) -> usize {
+        (self.image_width * self.image_height) as usize
+    }
+
+    /// The index into the rendered pixels of the pixel containing viewport coordinates `(s, t)`,
+    /// as passed to [`Camera::ray`].
+    pub fn pixel_index(&self, s: f64, t: f64) -> Option<usize> {
+        let x = (s * f64::from(self.image_width - 1)).floor();
+        let y = (t * f64::from(self.image_height - 1)).floor();
+
+        if x < 0.0 || y < 0.0 || x >= f64::from(self.image_width) {
+            return None;
+        }
+        let (x, y) = (x as u32, y as u32);
+        if y >= self.image_height {
+            return None;
+        }
+
+        Some(((self.image_height - y - 1) * self.image_width + x) as usize)
+    }
+
+    /// The area covered by the viewport coordinates of the pixels relative to the viewport, since
+    /// each pixel spans `1 / (width - 1)` of the viewport's width and `1 / (height - 1)` of its
+    /// height.
+    pub fn viewport_scale(&self) -> f64 {
+        let width = f64::from(self.image_width);
+        let height = f64::from(self.image_height);
+
+        width / (width - 1.0) * height / (height - 1.0)
+    }
+}
+
+/// A color and how opaque it is, for images composited over a background. The color is
+/// premultiplied by the opacity, so that the background shows through where it is less than 1.
+#[non_exhaustive]
+#[derive(Clone, Copy, Default)]
+pub struct Rgba {
+    pub color: Rgb,
+    pub alpha: f64,
+}
+
+impl Rgba {
+    /// Nothing but the background.
+    pub const TRANSPARENT: Self = Self::new(Rgb::ZERO, 0.0);
+
+    pub const fn new(color: Rgb, alpha: f64) -> Self {
+        Self { color, alpha }
+    }
+
+    pub const fn opaque(color: Rgb) -> Self {
+        Self::new(color, 1.0)
+    }
+
+    /// RGBA8 values of the average of `samples` summed samples, with the color divided by the
+    /// opacity and gamma-corrected.
+    pub fn to_rgba8(self, samples: u32) -> [u8; 4] {
+        let alpha = self.alpha / f64::from(samples);
+        let [r, g, b] = if self.alpha > 0.0 {
+            (self.color / self.alpha).to_rgb8(1)
+        } else {
+            [0; 3]
+        };
+
+        [r, g, b, (256.0 * alpha.clamp(0.0, 0.999)) as u8]
+    }
+
+    /// Linear RGBA values of the average of `samples` summed samples, with the color premultiplied
+    /// by the opacity.
+    pub fn to_rgba32f(self, samples: u32) -> [f32; 4] {
+        let scale = f64::from(samples).recip();
+        let color = scale * self.color;
+
+        [
+            color.x as f32,
+            color.y as f32,
+            color.z as f32,
+            (scale * self.alpha) as f32,
+        ]
+    }
+}
+
+impl AddAssign for Rgba {
+    fn add_assign(&mut self, rhs: Self) {
+        self.color += rhs.color;
+        self.alpha += rhs.alpha;
+    }
+}
+
+/// Colors added to arbitrary pixels from multiple threads.
+pub struct Splats(Vec<[AtomicU64; 3]>);
+
+impl Splats {
+    pub fn new(film: &Film) -> Self {
+        Self(
+            (0..film.image_width * film.image_height)
+                .map(|_| Default::default())
+                .collect(),
+        )
+    }
+
+    /// Add `color` to the pixel at `index`.
+    pub fn add(&self, index: usize, color: Rgb) {
+        for (channel, value) in self.0[index].iter().zip([color.x, color.y, color.z]) {
+            // There is no atomic float addition, so retry until no other thread has changed the
+            // value between loading and storing it.
+            let mut current = channel.load(Ordering::Relaxed);
+            while let Err(actual) = channel.compare_exchange_weak(
+                current,
+                (f64::from_bits(current) + value).to_bits(),
+                Ordering::Relaxed,
+                Ordering::Relaxed,
+            ) {
+                current = actual;
+            }
+        }
+    }
+
+    /// Add the splatted colors to `pixels`.
+    pub fn add_to(self, pixels: &mut [Rgb]) {
+        for (pixel, splat) in pixels.iter_mut().zip(self.0) {
+            let [r, g, b] = splat.map(|channel| f64::from_bits(channel.into_inner()));
+            *pixel += Rgb::newf(r, g, b);
+        }
+    }
+}
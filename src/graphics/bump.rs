@@ -0,0 +1,147 @@
+use std::sync::Arc;
+
+use rand::prelude::*;
+
+use crate::{
+    graphics::{Face, HitRecord, Material, Matte, Medium, Ray, Scatter, Texture},
+    math::{Onb, Rgb, Vec3},
+};
+
+/// How a [`Bumped`] material tilts the normal of a surface.
+#[derive(Clone)]
+pub enum Perturbation {
+    /// A tangent-space normal map, whose channels map [0, 1] to [-1, 1] along the directions of
+    /// increasing `u` and `v` and out of the surface.
+    NormalMap(Arc<dyn Texture>),
+    /// A height above the surface, read from the first channel and multiplied by `scale`.
+    Bump {
+        height: Arc<dyn Texture>,
+        scale: f64,
+    },
+}
+
+/// [`Material`] that shades a base material with normals tilted by a [`Perturbation`], for detail
+/// too fine to model.
+///
+/// Objects without tangents are tilted in an arbitrary frame around the normal. Light that the
+/// tilted normal would scatter to the other side of the actual surface is absorbed rather than
+/// leaking through it.
+#[non_exhaustive]
+pub struct Bumped {
+    pub base: Arc<dyn Material>,
+    pub perturbation: Perturbation,
+}
+
+impl Bumped {
+    pub const fn new(base: Arc<dyn Material>, perturbation: Perturbation) -> Self {
+        Self { base, perturbation }
+    }
+
+    pub const fn normal_map(base: Arc<dyn Material>, map: Arc<dyn Texture>) -> Self {
+        Self::new(base, Perturbation::NormalMap(map))
+    }
+
+    pub const fn bump(base: Arc<dyn Material>, height: Arc<dyn Texture>, scale: f64) -> Self {
+        Self::new(base, Perturbation::Bump { height, scale })
+    }
+
+    /// The intersection `rec` with its normal tilted.
+    fn shading<'a>(&self, rec: &HitRecord<'a>) -> HitRecord<'a> {
+        let n = rec.outward_normal();
+        let (dpdu, dpdv) = if rec.dpdu.cross(rec.dpdv).near_zero() {
+            let onb = Onb::from_w(n);
+            (onb.u, onb.v)
+        } else {
+            (rec.dpdu, rec.dpdv)
+        };
+
+        let normal = match &self.perturbation {
+            Perturbation::NormalMap(map) => {
+                let tangent = n.mul_add(-n.dot(dpdu), dpdu).unit();
+                let bitangent = n.cross(tangent);
+                let bitangent = if bitangent.dot(dpdv) < 0.0 {
+                    -bitangent
+                } else {
+                    bitangent
+                };
+                let c = map.value(rec.u, rec.v, &rec.point).mul_add(2.0, -Vec3::ONE);
+
+                tangent.mul_add(c.x, bitangent.mul_add(c.y, c.z * n))
+            }
+            Perturbation::Bump { height, scale } => {
+                // The step in texture coordinates for finite differences.
+                const DELTA: f64 = 0.0005;
+
+                let displacement = |u: f64, v: f64, p: Vec3| scale * height.value(u, v, &p).x;
+                let h = displacement(rec.u, rec.v, rec.point);
+                let dhdu = (displacement(rec.u + DELTA, rec.v, dpdu.mul_add(DELTA, rec.point)) - h)
+                    / DELTA;
+                let dhdv = (displacement(rec.u, rec.v + DELTA, dpdv.mul_add(DELTA, rec.point)) - h)
+                    / DELTA;
+
+                // The derivatives of the displaced surface, ignoring the change in the normal.
+                let normal = n.mul_add(dhdu, dpdu).cross(n.mul_add(dhdv, dpdv));
+                if normal.dot(n) < 0.0 { -normal } else { normal }
+            }
+        };
+        if normal.near_zero() || normal.dot(n) <= 0.0 {
+            return rec.clone();
+        }
+
+        let normal = normal.unit();
+        HitRecord {
+            normal: match rec.face {
+                Face::Front => normal,
+                Face::Back => -normal,
+            },
+            ..rec.clone()
+        }
+    }
+
+    /// Whether `direction` is on the same side of the actual surface at `rec` as of the tilted one.
+    fn same_side(rec: &HitRecord<'_>, shading: &HitRecord<'_>, direction: Vec3) -> bool {
+        (direction.dot(rec.normal) > 0.0) == (direction.dot(shading.normal) > 0.0)
+    }
+}
+
+impl Material for Bumped {
+    fn scatter(&self, r: &Ray, rec: &HitRecord<'_>, rng: &mut dyn RngCore) -> Option<Scatter> {
+        let shading = self.shading(rec);
+        let scatter = self.base.scatter(r, &shading, rng)?;
+
+        Self::same_side(rec, &shading, scatter.ray.direction).then_some(scatter)
+    }
+
+    fn attenuation(&self, r: &Ray, rec: &HitRecord<'_>, scattered: &Ray) -> Rgb {
+        let shading = self.shading(rec);
+        if !Self::same_side(rec, &shading, scattered.direction) {
+            return Rgb::ZERO;
+        }
+
+        self.base.attenuation(r, &shading, scattered)
+    }
+
+    fn scattering_pdf(&self, r: &Ray, rec: &HitRecord<'_>, scattered: &Ray) -> f64 {
+        self.base.scattering_pdf(r, &self.shading(rec), scattered)
+    }
+
+    fn emitted(&self, r: &Ray, rec: &HitRecord<'_>) -> Rgb {
+        self.base.emitted(r, rec)
+    }
+
+    fn is_diffuse(&self) -> bool {
+        self.base.is_diffuse()
+    }
+
+    fn is_dispersive(&self) -> bool {
+        self.base.is_dispersive()
+    }
+
+    fn medium(&self) -> Option<&Medium> {
+        self.base.medium()
+    }
+
+    fn matte(&self) -> Option<Matte> {
+        self.base.matte()
+    }
+}
@@ -90,18 +90,40 @@ impl Hit for Bvh {
         match &self.node {
             Node::Branch { left, right } => {
                 let left = left.hit(r, t_min, t_max);
+                // Only a hit on the right that is closer than the left can replace it.
+                let right = right.hit(r, t_min, left.as_ref().map_or(t_max, |rec| rec.t));
 
-                if left.is_some() {
-                    left
-                } else {
-                    right.hit(r, t_min, left.as_ref().map_or(t_max, |rec| rec.t))
-                }
+                right.or(left)
             }
-            Node::Leaf(node) => node.hit(r, t_min, t_max),
+            Node::Leaf(node) => node
+                .hit(r, t_min, t_max)
+                .map(|rec| rec.with_object(&**node)),
         }
     }
 
     fn bounding_box(&self, _: f64, _: f64) -> Option<Aabb> {
         Some(self.aabb)
     }
+
+    /// The same traversal as [`Bvh::hit`], kept separate so that rendering doesn't pay for
+    /// counting.
+    fn hit_with_visits(&self, r: &Ray, t_min: f64, t_max: f64) -> (Option<HitRecord<'_>>, usize) {
+        if !self.aabb.hit(r, t_min, t_max) {
+            return (None, 1);
+        }
+
+        match &self.node {
+            Node::Branch { left, right } => {
+                let (left, left_visits) = left.hit_with_visits(r, t_min, t_max);
+                let (right, right_visits) =
+                    right.hit_with_visits(r, t_min, left.as_ref().map_or(t_max, |rec| rec.t));
+
+                (right.or(left), 1 + left_visits + right_visits)
+            }
+            Node::Leaf(node) => {
+                let (rec, visits) = node.hit_with_visits(r, t_min, t_max);
+                (rec.map(|rec| rec.with_object(&**node)), 1 + visits)
+            }
+        }
+    }
 }
@@ -1,6 +1,10 @@
+use std::ptr;
+
+use rand::prelude::*;
+
 use crate::{
     graphics::{Aabb, Material, Ray},
-    math::{Point, Vec3},
+    math::{Onb, Point, Vec3},
 };
 
 /// Abstraction for objects whose surface may intersect a [`Ray`].
@@ -9,6 +13,35 @@ pub trait Hit: Send + Sync {
     /// resulting intersection.
     fn hit(&self, r: &Ray, t_min: f64, t_max: f64) -> Option<HitRecord<'_>>;
     fn bounding_box(&self, t_start: f64, t_end: f64) -> Option<Aabb>;
+
+    /// The probability density, with respect to solid angle at `origin`, of [`Hit::random`]
+    /// sampling `direction`.
+    fn pdf_value(&self, _origin: Point, _direction: Vec3) -> f64 {
+        0.0
+    }
+
+    /// A random direction from `origin` towards the object's surface.
+    fn random(&self, _origin: Point, _rng: &mut dyn RngCore) -> Vec3 {
+        Vec3::newi(1, 0, 0)
+    }
+
+    /// A random point on the object's surface, as a record facing outwards, and the probability
+    /// density with respect to area of sampling it.
+    fn sample_surface(&self, _rng: &mut dyn RngCore) -> Option<(HitRecord<'_>, f64)> {
+        None
+    }
+
+    /// The probability density, with respect to area, of [`Hit::sample_surface`] sampling the
+    /// point where a ray from `origin` along `direction` first hits the object.
+    fn surface_pdf(&self, _origin: Point, _direction: Vec3) -> f64 {
+        0.0
+    }
+
+    /// [`Hit::hit`], and the number of [`Bvh`](crate::graphics::Bvh) nodes visited to find the
+    /// intersection, for measuring traversal cost.
+    fn hit_with_visits(&self, r: &Ray, t_min: f64, t_max: f64) -> (Option<HitRecord<'_>>, usize) {
+        (self.hit(r, t_min, t_max), 0)
+    }
 }
 
 impl Hit for Box<dyn Hit> {
@@ -19,10 +52,31 @@ impl Hit for Box<dyn Hit> {
     fn bounding_box(&self, t_start: f64, t_end: f64) -> Option<Aabb> {
         (**self).bounding_box(t_start, t_end)
     }
+
+    fn pdf_value(&self, origin: Point, direction: Vec3) -> f64 {
+        (**self).pdf_value(origin, direction)
+    }
+
+    fn random(&self, origin: Point, rng: &mut dyn RngCore) -> Vec3 {
+        (**self).random(origin, rng)
+    }
+
+    fn sample_surface(&self, rng: &mut dyn RngCore) -> Option<(HitRecord<'_>, f64)> {
+        (**self).sample_surface(rng)
+    }
+
+    fn surface_pdf(&self, origin: Point, direction: Vec3) -> f64 {
+        (**self).surface_pdf(origin, direction)
+    }
+
+    fn hit_with_visits(&self, r: &Ray, t_min: f64, t_max: f64) -> (Option<HitRecord<'_>>, usize) {
+        (**self).hit_with_visits(r, t_min, t_max)
+    }
 }
 
 /// A record of a ray-object intersection. The `mat` field is a `&dyn Material` to avoid atomic
 /// operations in loops (e.g. cloning an `Arc<dyn Material>`).
+#[derive(Clone)]
 pub struct HitRecord<'a> {
     pub point: Point,
     pub normal: Vec3,
@@ -31,9 +85,17 @@ pub struct HitRecord<'a> {
     pub u: f64,
     pub v: f64,
     pub face: Face,
+    /// The derivatives of `point` with respect to `u` and `v`, tangent to the surface, or zero
+    /// where the object has no parametrization.
+    pub dpdu: Vec3,
+    pub dpdv: Vec3,
+    /// An identifier of the innermost object in a [`HitList`] or [`Bvh`](crate::graphics::Bvh)
+    /// that was hit, or 0 if the object was not in a container.
+    pub object: usize,
 }
 
 /// The front or back of an object's surface.
+#[derive(Clone, Copy, PartialEq, Eq)]
 pub enum Face {
     Front,
     Back,
@@ -57,6 +119,53 @@ impl<'a> HitRecord<'a> {
             u,
             v,
             face,
+            dpdu: Vec3::ZERO,
+            dpdv: Vec3::ZERO,
+            object: 0,
+        }
+    }
+
+    /// Set the derivatives of the point with respect to `u` and `v`.
+    #[must_use]
+    pub const fn with_tangents(mut self, dpdu: Vec3, dpdv: Vec3) -> Self {
+        self.dpdu = dpdu;
+        self.dpdv = dpdv;
+        self
+    }
+
+    /// Identify the object that was hit with the address of `object`, unless an object nested
+    /// inside it was already identified.
+    #[must_use]
+    pub fn with_object(mut self, object: &dyn Hit) -> Self {
+        if self.object == 0 {
+            self.object = ptr::from_ref(object).cast::<()>().addr();
+        }
+        self
+    }
+
+    /// The frame in which microfacet materials are shaded, around the normal and oriented along
+    /// the direction of increasing `u` if the object has tangents.
+    pub fn shading_frame(&self) -> Onb {
+        Onb::from_w_tangent(self.normal, self.dpdu)
+    }
+
+    /// The normal pointing out of the object, whichever face was hit.
+    pub fn outward_normal(&self) -> Vec3 {
+        match self.face {
+            Face::Front => self.normal,
+            Face::Back => -self.normal,
+        }
+    }
+
+    /// The same intersection seen by the [`Ray`] `r`, with the face and normal flipped if `r`
+    /// arrives from the other side of the surface.
+    #[must_use]
+    pub fn facing(&self, r: &Ray) -> Self {
+        let (face, normal) = Self::face_normal(r, self.outward_normal());
+        Self {
+            face,
+            normal,
+            ..self.clone()
         }
     }
 
@@ -82,7 +191,7 @@ impl Hit for HitList {
         for object in self {
             if let Some(hit) = object.hit(r, t_min, closest_so_far) {
                 closest_so_far = hit.t;
-                rec = Some(hit);
+                rec = Some(hit.with_object(&**object));
             }
         }
 
@@ -109,6 +218,61 @@ impl Hit for HitList {
 
         Some(box_)
     }
+
+    /// The average of each object's density, since `random` picks an object uniformly.
+    fn pdf_value(&self, origin: Point, direction: Vec3) -> f64 {
+        let weight = (self.len() as f64).recip();
+
+        self.iter()
+            .map(|object| weight * object.pdf_value(origin, direction))
+            .sum()
+    }
+
+    fn random(&self, origin: Point, rng: &mut dyn RngCore) -> Vec3 {
+        self[rng.random_range(0..self.len())].random(origin, rng)
+    }
+
+    /// A point on an object picked uniformly.
+    fn sample_surface(&self, rng: &mut dyn RngCore) -> Option<(HitRecord<'_>, f64)> {
+        let object = &self[rng.random_range(0..self.len())];
+        let (rec, pdf) = object.sample_surface(rng)?;
+
+        Some((rec.with_object(&**object), pdf / self.len() as f64))
+    }
+
+    fn surface_pdf(&self, origin: Point, direction: Vec3) -> f64 {
+        let r = Ray::new(origin, direction, 0.0);
+        let mut closest = None;
+        let mut closest_so_far = f64::INFINITY;
+
+        for object in self {
+            if let Some(hit) = object.hit(&r, 0.001, closest_so_far) {
+                closest_so_far = hit.t;
+                closest = Some(object);
+            }
+        }
+
+        closest.map_or(0.0, |object| {
+            object.surface_pdf(origin, direction) / self.len() as f64
+        })
+    }
+
+    fn hit_with_visits(&self, r: &Ray, t_min: f64, t_max: f64) -> (Option<HitRecord<'_>>, usize) {
+        let mut rec = None;
+        let mut closest_so_far = t_max;
+        let mut visits = 0;
+
+        for object in self {
+            let (hit, object_visits) = object.hit_with_visits(r, t_min, closest_so_far);
+            visits += object_visits;
+            if let Some(hit) = hit {
+                closest_so_far = hit.t;
+                rec = Some(hit.with_object(&**object));
+            }
+        }
+
+        (rec, visits)
+    }
 }
 
 /// Creates a `Vec` of objects that implement [`Hit`].
@@ -0,0 +1,111 @@
+use std::cmp::Ordering;
+
+use strum::IntoEnumIterator;
+
+use crate::math::{Axis, Point};
+
+/// A balanced k-d tree of items at points, for finding the items near a point.
+///
+/// The tree is stored implicitly: the median of each slice of nodes is the root of the subtree
+/// made by that slice, split along the axis on which the slice's points are most spread out.
+pub struct KdTree<T> {
+    nodes: Vec<Node<T>>,
+}
+
+struct Node<T> {
+    point: Point,
+    axis: Axis,
+    item: T,
+}
+
+impl<T> KdTree<T> {
+    pub fn new(items: impl IntoIterator<Item = (Point, T)>) -> Self {
+        let mut nodes: Vec<_> = items
+            .into_iter()
+            .map(|(point, item)| Node {
+                point,
+                axis: Axis::X,
+                item,
+            })
+            .collect();
+        Self::build(&mut nodes);
+
+        Self { nodes }
+    }
+
+    pub const fn len(&self) -> usize {
+        self.nodes.len()
+    }
+
+    pub const fn is_empty(&self) -> bool {
+        self.nodes.is_empty()
+    }
+
+    /// Call `f` with each item whose point is within `radius` of `center`.
+    pub fn within(&self, center: Point, radius: f64, mut f: impl FnMut(Point, &T)) {
+        Self::search(&self.nodes, center, radius * radius, &mut f);
+    }
+
+    fn build(nodes: &mut [Node<T>]) {
+        if nodes.len() <= 1 {
+            return;
+        }
+
+        let axis = Axis::iter()
+            .max_by(|&a, &b| Self::extent(nodes, a).total_cmp(&Self::extent(nodes, b)))
+            .unwrap_or(Axis::X);
+        let mid = nodes.len() / 2;
+        nodes.select_nth_unstable_by(mid, |l, r| {
+            l.point[axis]
+                .partial_cmp(&r.point[axis])
+                .unwrap_or(Ordering::Equal)
+        });
+        nodes[mid].axis = axis;
+
+        let (left, right) = nodes.split_at_mut(mid);
+        Self::build(left);
+        Self::build(&mut right[1..]);
+    }
+
+    fn extent(nodes: &[Node<T>], axis: Axis) -> f64 {
+        let (min, max) = nodes
+            .iter()
+            .fold((f64::INFINITY, f64::NEG_INFINITY), |(min, max), node| {
+                (min.min(node.point[axis]), max.max(node.point[axis]))
+            });
+        max - min
+    }
+
+    fn search(
+        nodes: &[Node<T>],
+        center: Point,
+        radius_squared: f64,
+        f: &mut impl FnMut(Point, &T),
+    ) {
+        if nodes.is_empty() {
+            return;
+        }
+
+        let mid = nodes.len() / 2;
+        let node = &nodes[mid];
+        if (node.point - center).len_squared() <= radius_squared {
+            f(node.point, &node.item);
+        }
+        if nodes.len() == 1 {
+            return;
+        }
+
+        // Search the side of the splitting plane containing the center first, and the other side
+        // only if the sphere crosses the plane.
+        let offset = center[node.axis] - node.point[node.axis];
+        let (near, far) = if offset < 0.0 {
+            (&nodes[..mid], &nodes[mid + 1..])
+        } else {
+            (&nodes[mid + 1..], &nodes[..mid])
+        };
+        Self::search(near, center, radius_squared, f);
+        if offset * offset <= radius_squared {
+            Self::search(far, center, radius_squared, f);
+        }
+    }
+}
@@ -1,18 +1,75 @@
-use std::sync::Arc;
+use std::{f64::consts::PI, sync::Arc};
 
 use rand::prelude::*;
 
 use crate::{
-    graphics::{Face, HitRecord, Ray, Texture},
+    graphics::{
+        CosinePdf, Face, HitRecord, Medium, Pdf, Ray, Solid, Texture, ThinFilm, TrowbridgeReitz,
+        fresnel_conductor, fresnel_dielectric, refract,
+    },
     math::{Rgb, Vec3},
     rng::CLOSED_OPEN_01,
+    spectrum::{self, Dispersion},
 };
 
 /// Trait for object materials to define how they scatter [`Ray`]s.
 pub trait Material: Send + Sync {
-    /// Calculate a scattered [`Ray`] and its resulting color attenuation from a ray-object
+    /// Sample a scattered [`Ray`] and its resulting color attenuation from a ray-object
     /// intersection.
-    fn scatter(&self, r: &Ray, rec: &HitRecord<'_>, rng: &mut ThreadRng) -> Option<Scatter>;
+    fn scatter(&self, r: &Ray, rec: &HitRecord<'_>, rng: &mut dyn RngCore) -> Option<Scatter>;
+
+    /// The color attenuation of light scattered from the direction of `scattered` towards the
+    /// origin of `r`; the product of the BSDF and the cosine of the angle between `scattered` and
+    /// the surface normal. Always zero for specular materials.
+    fn attenuation(&self, _r: &Ray, _rec: &HitRecord<'_>, _scattered: &Ray) -> Rgb {
+        Rgb::ZERO
+    }
+
+    /// The probability density, with respect to solid angle, of [`Material::scatter`] sampling the
+    /// direction of `scattered`. Always zero for specular materials.
+    fn scattering_pdf(&self, _r: &Ray, _rec: &HitRecord<'_>, _scattered: &Ray) -> f64 {
+        0.0
+    }
+
+    /// The color of light emitted towards the origin of `r`.
+    fn emitted(&self, _r: &Ray, _rec: &HitRecord<'_>) -> Rgb {
+        Rgb::ZERO
+    }
+
+    /// Whether any of the light scattered by the material is spread out diffusely, rather than
+    /// only in specular or glossy lobes around the mirror and refracted directions.
+    fn is_diffuse(&self) -> bool {
+        true
+    }
+
+    /// Whether light of different wavelengths scatters differently, like in different directions,
+    /// so that only the hero wavelength of [`Ray::wavelength`] can be followed.
+    fn is_dispersive(&self) -> bool {
+        false
+    }
+
+    /// The medium filling the object, which light arriving at the back of the surface has
+    /// travelled through.
+    fn medium(&self) -> Option<&Medium> {
+        None
+    }
+
+    /// How the material is composited over a background where the camera sees it directly, if it
+    /// stands in for part of the background.
+    fn matte(&self) -> Option<Matte> {
+        None
+    }
+}
+
+/// How a material seen directly by the camera is composited over a background, such as a
+/// photograph the scene is rendered into.
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum Matte {
+    /// Cut out to the background.
+    Holdout,
+    /// Show the background, darkened by the shadows falling on the surface and brightened by the
+    /// light it reflects from the rest of the scene.
+    ShadowCatcher,
 }
 
 /// Creates a `HashMap` with `String` keys and `Arc<dyn Material>` values.
@@ -35,13 +92,34 @@ macro_rules! matlist {
 pub struct Scatter {
     /// The scattered ray.
     pub ray: Ray,
-    /// The color of the scattered ray.
+    /// The color of the scattered ray, already divided by `pdf`: the light arriving along `ray` is
+    /// multiplied by `attenuation`.
     pub attenuation: Rgb,
+    /// The probability density with which the direction of `ray` was sampled. Meaningless for
+    /// specular scatters.
+    pub pdf: f64,
+    /// Whether the ray was scattered by a perfectly specular lobe, which cannot be evaluated for
+    /// any other direction.
+    pub specular: bool,
 }
 
 impl Scatter {
-    pub const fn new(ray: Ray, attenuation: Rgb) -> Self {
-        Self { ray, attenuation }
+    pub const fn new(ray: Ray, attenuation: Rgb, pdf: f64) -> Self {
+        Self {
+            ray,
+            attenuation,
+            pdf,
+            specular: false,
+        }
+    }
+
+    pub const fn specular(ray: Ray, attenuation: Rgb) -> Self {
+        Self {
+            ray,
+            attenuation,
+            pdf: 0.0,
+            specular: true,
+        }
     }
 }
 
@@ -59,20 +137,31 @@ impl Lambertian {
 }
 
 impl Material for Lambertian {
-    fn scatter(&self, r: &Ray, rec: &HitRecord<'_>, rng: &mut ThreadRng) -> Option<Scatter> {
-        let mut direction = rec.normal + Vec3::random_unit_vec(rng);
+    fn scatter(&self, r: &Ray, rec: &HitRecord<'_>, rng: &mut dyn RngCore) -> Option<Scatter> {
+        let pdf = CosinePdf::new(rec.normal);
+        let scattered = Ray::new(rec.point, pdf.generate(rng), r.t);
+        let pdf = pdf.value(scattered.direction);
 
         // Catch degenerate scatter direction.
-        if direction.near_zero() {
-            direction = rec.normal;
+        if pdf <= 0.0 {
+            return None;
         }
 
-        let scattered = Ray::new(rec.point, direction, r.t);
+        // The albedo times the cosine-weighted BSDF, divided by the cosine-weighted pdf.
         Some(Scatter::new(
             scattered,
             self.albedo.value(rec.u, rec.v, &rec.point),
+            pdf,
         ))
     }
+
+    fn attenuation(&self, r: &Ray, rec: &HitRecord<'_>, scattered: &Ray) -> Rgb {
+        self.albedo.value(rec.u, rec.v, &rec.point) * self.scattering_pdf(r, rec, scattered)
+    }
+
+    fn scattering_pdf(&self, _r: &Ray, rec: &HitRecord<'_>, scattered: &Ray) -> f64 {
+        CosinePdf::new(rec.normal).value(scattered.direction)
+    }
 }
 
 /// [`Material`] with metallic reflection.
@@ -91,10 +180,30 @@ impl Metallic {
             blur: blur.min(1.0),
         }
     }
+
+    /// The probability density, with respect to solid angle, of sampling `direction` as the unit
+    /// vector `reflected` offset by a uniformly distributed point in a ball of radius `blur`.
+    fn blur_pdf(&self, reflected: Vec3, direction: Vec3) -> f64 {
+        // The density along `direction` is the ball's uniform volume density integrated over the
+        // segment of the ray from the origin that lies in the ball, weighted by distance squared.
+        let cos = direction.unit().dot(reflected);
+        let discriminant = cos.mul_add(cos, self.blur.mul_add(self.blur, -1.0));
+        if discriminant < 0.0 {
+            return 0.0;
+        }
+
+        let t_far = cos + discriminant.sqrt();
+        let t_near = (cos - discriminant.sqrt()).max(0.0);
+        if t_far <= 0.0 {
+            return 0.0;
+        }
+
+        (t_far.powi(3) - t_near.powi(3)) / (4.0 * PI * self.blur.powi(3))
+    }
 }
 
 impl Material for Metallic {
-    fn scatter(&self, r: &Ray, rec: &HitRecord<'_>, rng: &mut ThreadRng) -> Option<Scatter> {
+    fn scatter(&self, r: &Ray, rec: &HitRecord<'_>, rng: &mut dyn RngCore) -> Option<Scatter> {
         let reflected = r.direction.unit().reflect(rec.normal);
         let scattered = Ray::new(
             rec.point,
@@ -102,52 +211,856 @@ impl Material for Metallic {
             r.t,
         );
 
-        if scattered.direction.dot(rec.normal) > 0.0 {
-            Some(Scatter::new(scattered, self.albedo))
-        } else {
+        if scattered.direction.dot(rec.normal) <= 0.0 {
             None
+        } else if self.blur > 0.0 {
+            let pdf = self.blur_pdf(reflected, scattered.direction);
+            Some(Scatter::new(scattered, self.albedo, pdf))
+        } else {
+            Some(Scatter::specular(scattered, self.albedo))
         }
     }
+
+    fn attenuation(&self, r: &Ray, rec: &HitRecord<'_>, scattered: &Ray) -> Rgb {
+        if scattered.direction.dot(rec.normal) <= 0.0 {
+            return Rgb::ZERO;
+        }
+
+        self.albedo * self.scattering_pdf(r, rec, scattered)
+    }
+
+    fn scattering_pdf(&self, r: &Ray, rec: &HitRecord<'_>, scattered: &Ray) -> f64 {
+        if self.blur > 0.0 {
+            self.blur_pdf(r.direction.unit().reflect(rec.normal), scattered.direction)
+        } else {
+            0.0
+        }
+    }
+
+    fn is_diffuse(&self) -> bool {
+        false
+    }
 }
 
-/// [`Material`] with dielectric refraction.
+/// [`Material`] with the rough reflection of a metal, from a GGX microfacet model with a complex
+/// refractive index.
+#[non_exhaustive]
+pub struct Conductor {
+    /// The real part of the refractive index, per color channel.
+    pub eta: Rgb,
+    /// The imaginary part of the refractive index, the absorption coefficient, per color channel.
+    pub k: Rgb,
+    pub distribution: TrowbridgeReitz,
+    /// A transparent film over the metal, like the oxide layer of anodized metal.
+    pub film: Option<ThinFilm>,
+}
+
+impl Conductor {
+    /// A conductor with the refractive index `eta` + *i*`k` and a perceptual `roughness` in
+    /// [0, 1], where 0 is a perfect mirror.
+    pub fn new(eta: Rgb, k: Rgb, roughness: f64) -> Self {
+        Self {
+            eta,
+            k,
+            distribution: TrowbridgeReitz::from_roughness(roughness, roughness),
+            film: None,
+        }
+    }
+
+    /// Use different roughnesses along the directions of increasing `u` and `v` on the surface,
+    /// stretching highlights like those of brushed metal. Objects without tangents are shaded in
+    /// an arbitrary frame around the normal.
+    #[must_use]
+    pub fn with_anisotropic_roughness(mut self, roughness_u: f64, roughness_v: f64) -> Self {
+        self.distribution = TrowbridgeReitz::from_roughness(roughness_u, roughness_v);
+        self
+    }
+
+    /// Cover the metal with a thin film, whose interference colors its reflections.
+    #[must_use]
+    pub fn with_thin_film(mut self, film: ThinFilm) -> Self {
+        self.film = Some(film);
+        self
+    }
+
+    pub fn gold(roughness: f64) -> Self {
+        Self::new(
+            Rgb::newf(0.143, 0.374, 1.442),
+            Rgb::newf(3.983, 2.385, 1.603),
+            roughness,
+        )
+    }
+
+    pub fn copper(roughness: f64) -> Self {
+        Self::new(
+            Rgb::newf(0.200, 0.924, 1.102),
+            Rgb::newf(3.912, 2.452, 2.142),
+            roughness,
+        )
+    }
+
+    pub fn aluminium(roughness: f64) -> Self {
+        Self::new(
+            Rgb::newf(1.657, 0.880, 0.521),
+            Rgb::newf(9.224, 6.270, 4.837),
+            roughness,
+        )
+    }
+
+    pub fn titanium(roughness: f64) -> Self {
+        Self::new(
+            Rgb::newf(2.741, 2.542, 2.138),
+            Rgb::newf(3.814, 3.435, 2.918),
+            roughness,
+        )
+    }
+
+    pub fn silver(roughness: f64) -> Self {
+        Self::new(
+            Rgb::newf(0.155, 0.117, 0.138),
+            Rgb::newf(4.828, 3.122, 2.147),
+            roughness,
+        )
+    }
+
+    /// The reflectance at `rec` for light arriving at the cosine `cos` from the normal, at the
+    /// wavelength of `r`.
+    fn fresnel(&self, r: &Ray, rec: &HitRecord<'_>, cos: f64) -> Rgb {
+        if let Some(film) = &self.film {
+            return film.reflectance(rec, r.wavelength, cos, 1.0, |lambda| {
+                (
+                    spectrum::interpolate(self.eta, lambda),
+                    spectrum::interpolate(self.k, lambda),
+                )
+            });
+        }
+
+        Rgb::newf(
+            fresnel_conductor(cos, self.eta.x, self.k.x),
+            fresnel_conductor(cos, self.eta.y, self.k.y),
+            fresnel_conductor(cos, self.eta.z, self.k.z),
+        )
+    }
+
+    /// The shading frame at `rec`, and the directions towards the origin of `r` and along
+    /// `scattered` within it, if both are above the surface.
+    fn local(r: &Ray, rec: &HitRecord<'_>, scattered: &Ray) -> Option<(Vec3, Vec3)> {
+        let uvw = rec.shading_frame();
+        let wo = uvw.to_local(-r.direction.unit());
+        let wi = uvw.to_local(scattered.direction.unit());
+
+        (wo.z > 0.0 && wi.z > 0.0).then_some((wo, wi))
+    }
+
+    /// The BSDF times the cosine of `wi`, and the density of sampling `wi`.
+    fn evaluate(&self, r: &Ray, rec: &HitRecord<'_>, wo: Vec3, wi: Vec3) -> (Rgb, f64) {
+        let wm = (wo + wi).unit();
+        let cos = wo.dot(wm);
+        if cos <= 0.0 {
+            return (Rgb::ZERO, 0.0);
+        }
+        let dist = self.distribution;
+        let f = dist.d(wm) * dist.g(wo, wi) / (4.0 * wo.z) * self.fresnel(r, rec, cos);
+
+        (f, dist.d_visible(wo, wm) / (4.0 * cos))
+    }
+}
+
+impl Material for Conductor {
+    fn scatter(&self, r: &Ray, rec: &HitRecord<'_>, rng: &mut dyn RngCore) -> Option<Scatter> {
+        let uvw = rec.shading_frame();
+        let wo = uvw.to_local(-r.direction.unit());
+        if wo.z <= 0.0 {
+            return None;
+        }
+
+        if self.distribution.is_smooth() {
+            let wi = Vec3::newf(-wo.x, -wo.y, wo.z);
+            let scattered = Ray::new(rec.point, uvw.local(wi), r.t);
+            return Some(Scatter::specular(scattered, self.fresnel(r, rec, wo.z)));
+        }
+
+        let wm = self.distribution.sample_visible(wo, rng);
+        let wi = (-wo).reflect(wm);
+        if wi.z <= 0.0 {
+            return None;
+        }
+        let (f, pdf) = self.evaluate(r, rec, wo, wi);
+        if pdf <= 0.0 {
+            return None;
+        }
+
+        Some(Scatter::new(
+            Ray::new(rec.point, uvw.local(wi), r.t),
+            f / pdf,
+            pdf,
+        ))
+    }
+
+    fn attenuation(&self, r: &Ray, rec: &HitRecord<'_>, scattered: &Ray) -> Rgb {
+        if self.distribution.is_smooth() {
+            return Rgb::ZERO;
+        }
+
+        Self::local(r, rec, scattered).map_or(Rgb::ZERO, |(wo, wi)| self.evaluate(r, rec, wo, wi).0)
+    }
+
+    fn scattering_pdf(&self, r: &Ray, rec: &HitRecord<'_>, scattered: &Ray) -> f64 {
+        if self.distribution.is_smooth() {
+            return 0.0;
+        }
+
+        Self::local(r, rec, scattered).map_or(0.0, |(wo, wi)| self.evaluate(r, rec, wo, wi).1)
+    }
+
+    fn is_dispersive(&self) -> bool {
+        self.film.is_some()
+    }
+
+    fn is_diffuse(&self) -> bool {
+        false
+    }
+}
+
+/// [`Material`] with dielectric refraction, through a smooth interface or a rough one from a GGX
+/// microfacet model.
 #[non_exhaustive]
 pub struct Dielectric {
     /// The refractive index of the material.
     pub idx: f64,
+    pub distribution: TrowbridgeReitz,
+    /// The fraction of light absorbed per unit distance travelled inside the material, per color
+    /// channel.
+    pub absorption: Rgb,
+    /// How the refractive index varies with wavelength, when rendering spectra.
+    pub dispersion: Option<Dispersion>,
+    /// A transparent film over the surface, like a soap film over a refractive index of 1.
+    pub film: Option<ThinFilm>,
 }
 
 impl Dielectric {
     pub const fn new(idx: f64) -> Self {
-        Self { idx }
+        Self {
+            idx,
+            distribution: TrowbridgeReitz::new(0.0, 0.0),
+            absorption: Rgb::ZERO,
+            dispersion: None,
+            film: None,
+        }
+    }
+
+    /// A dielectric whose refractive index varies with wavelength, splitting white light into
+    /// colors. Without a spectrum, the index is that at the sodium D line.
+    pub fn dispersive(dispersion: Dispersion) -> Self {
+        let mut dielectric = Self::new(dispersion.idx(spectrum::LAMBDA_D));
+        dielectric.dispersion = Some(dispersion);
+        dielectric
+    }
+
+    /// Roughen the interface with a perceptual `roughness` in [0, 1], like frosted glass.
+    #[must_use]
+    pub fn with_roughness(mut self, roughness: f64) -> Self {
+        self.distribution = TrowbridgeReitz::from_roughness(roughness, roughness);
+        self
+    }
+
+    /// Absorb light travelling through the material following the Beer–Lambert law, with the
+    /// `absorption` coefficient per unit distance.
+    #[must_use]
+    pub const fn with_absorption(mut self, absorption: Rgb) -> Self {
+        self.absorption = absorption;
+        self
+    }
+
+    /// Tint the material so that light travelling `distance` through it keeps the fraction
+    /// `transmittance` of its color.
+    #[must_use]
+    pub fn with_transmittance(self, transmittance: Rgb, distance: f64) -> Self {
+        let absorption = |t: f64| -t.clamp(f64::MIN_POSITIVE, 1.0).ln() / distance;
+        self.with_absorption(Rgb::newf(
+            absorption(transmittance.x),
+            absorption(transmittance.y),
+            absorption(transmittance.z),
+        ))
     }
 
-    fn reflectance(cos: f64, idx: f64) -> f64 {
-        // Schlick's approximation for reflectance.
-        let r0 = ((1.0 - idx) / (1.0 + idx)).powi(2);
-        // r0 + (1.0 - r0) * (1.0 - cos).powi(5)
-        (1.0 - r0).mul_add((1.0 - cos).powi(5), r0)
+    /// Cover the surface with a thin film, whose interference colors its reflections.
+    #[must_use]
+    pub fn with_thin_film(mut self, film: ThinFilm) -> Self {
+        self.film = Some(film);
+        self
+    }
+
+    /// The fraction of light surviving the path of `r` to `rec`, which was inside the material if
+    /// it hit the back of the surface.
+    fn transmittance(&self, r: &Ray, rec: &HitRecord<'_>) -> Rgb {
+        if rec.face == Face::Front || self.absorption.near_zero() {
+            return Rgb::ONE;
+        }
+        let distance = rec.t * r.direction.len();
+
+        Rgb::newf(
+            (-self.absorption.x * distance).exp(),
+            (-self.absorption.y * distance).exp(),
+            (-self.absorption.z * distance).exp(),
+        )
+    }
+
+    /// The refractive index for the wavelength of `r`.
+    fn idx(&self, r: &Ray) -> f64 {
+        match (self.dispersion, r.wavelength) {
+            (Some(dispersion), Some(wavelength)) => dispersion.idx(wavelength),
+            _ => self.idx,
+        }
+    }
+
+    /// The ratio of the refractive index on the far side of the surface at `rec` to that on the
+    /// near side, for the wavelength of `r`.
+    fn eta(&self, r: &Ray, rec: &HitRecord<'_>) -> f64 {
+        match rec.face {
+            Face::Front => self.idx(r),
+            Face::Back => self.idx(r).recip(),
+        }
+    }
+
+    /// The reflectance at `rec` for light arriving at the cosine `cos` from the normal, through
+    /// the film if there is one.
+    fn reflectance(&self, r: &Ray, rec: &HitRecord<'_>, cos: f64, eta: f64) -> Rgb {
+        let fresnel = fresnel_dielectric(cos, eta);
+        let Some(film) = &self.film else {
+            return Rgb::new_all(fresnel);
+        };
+        // Nothing crosses the film into a medium the light cannot refract into.
+        if fresnel >= 1.0 {
+            return Rgb::ONE;
+        }
+        let (above, below) = match rec.face {
+            Face::Front => (1.0, self.idx(r)),
+            Face::Back => (self.idx(r), 1.0),
+        };
+
+        film.reflectance(rec, r.wavelength, cos, above, |_| (below, 0.0))
+    }
+
+    /// The probability of sampling reflection rather than refraction from a surface with the given
+    /// reflectance.
+    const fn reflect_probability(reflectance: Rgb) -> f64 {
+        reflectance.mean()
+    }
+
+    /// The BSDF times the cosine of `wi`, and the density of sampling `wi`, in the shading frame
+    /// where `wo` is above the surface.
+    ///
+    /// Radiance is not scaled by the squared ratio of refractive indices as it crosses the
+    /// interface, like the smooth interface. The transmission term is therefore the adjoint form
+    /// meant for importance, and is not symmetric: swapping the directions scales it by `eta`
+    /// squared. Camera paths still come out right as long as they leave the object through the
+    /// same kind of interface they entered by, since the factors cancel.
+    pub(super) fn evaluate(&self, eta: f64, wo: Vec3, wi: Vec3) -> (f64, f64) {
+        let (f, pdf) = self.evaluate_with(eta, wo, wi, |cos| {
+            Rgb::new_all(fresnel_dielectric(cos, eta))
+        });
+        (f.x, pdf)
+    }
+
+    /// [`Dielectric::evaluate`] with the reflectance per color channel of microfacets seen at each
+    /// cosine given by `fresnel`.
+    fn evaluate_with(
+        &self,
+        eta: f64,
+        wo: Vec3,
+        wi: Vec3,
+        fresnel: impl Fn(f64) -> Rgb,
+    ) -> (Rgb, f64) {
+        let reflect = wi.z > 0.0;
+        let etap = if reflect { 1.0 } else { eta };
+        // The generalized half vector, facing the side of `wo`.
+        let wm = wi.mul_add(etap, wo);
+        if wi.z == 0.0 || wm.len_squared() == 0.0 {
+            return (Rgb::ZERO, 0.0);
+        }
+        let mut wm = wm.unit();
+        if wm.z < 0.0 {
+            wm = -wm;
+        }
+        let (cos_o, cos_i) = (wo.dot(wm), wi.dot(wm));
+        // Discard microfacets seen from behind.
+        if cos_o <= 0.0 || cos_i * wi.z < 0.0 {
+            return (Rgb::ZERO, 0.0);
+        }
+
+        let dist = self.distribution;
+        let r = fresnel(cos_o);
+        let p = Self::reflect_probability(r);
+        if reflect {
+            let f = dist.d(wm) * dist.g(wo, wi) / (4.0 * wo.z) * r;
+            (f, p * dist.d_visible(wo, wm) / (4.0 * cos_o))
+        } else {
+            let denominator = cos_i.mul_add(eta, cos_o).powi(2);
+            let dwm_dwi = eta * eta * cos_i.abs() / denominator;
+            let f = dist.d(wm) * dist.g(wo, wi) * cos_o * dwm_dwi / wo.z * (Rgb::ONE - r);
+            (f, (1.0 - p) * dist.d_visible(wo, wm) * dwm_dwi)
+        }
+    }
+
+    /// A direction reflected or refracted by the rough interface, in the shading frame where `wo`
+    /// is above the surface.
+    pub(super) fn sample(&self, eta: f64, wo: Vec3, rng: &mut dyn RngCore) -> Option<Vec3> {
+        self.sample_with(
+            eta,
+            wo,
+            |cos| Rgb::new_all(fresnel_dielectric(cos, eta)),
+            rng,
+        )
+    }
+
+    /// [`Dielectric::sample`] with the reflectance per color channel of microfacets seen at each
+    /// cosine given by `fresnel`.
+    fn sample_with(
+        &self,
+        eta: f64,
+        wo: Vec3,
+        fresnel: impl Fn(f64) -> Rgb,
+        rng: &mut dyn RngCore,
+    ) -> Option<Vec3> {
+        let wm = self.distribution.sample_visible(wo, rng);
+        if Self::reflect_probability(fresnel(wo.dot(wm))) > CLOSED_OPEN_01.sample(rng) {
+            Some((-wo).reflect(wm)).filter(|wi| wi.z > 0.0)
+        } else {
+            refract(wo, wm, eta).filter(|wi| wi.z < 0.0)
+        }
+    }
+
+    /// The shading frame at `rec`, and the directions towards the origin of `r` and along
+    /// `scattered` within it.
+    fn local(r: &Ray, rec: &HitRecord<'_>, scattered: &Ray) -> (Vec3, Vec3) {
+        let uvw = rec.shading_frame();
+        (
+            uvw.to_local(-r.direction.unit()),
+            uvw.to_local(scattered.direction.unit()),
+        )
     }
 }
 
 impl Material for Dielectric {
-    fn scatter(&self, r: &Ray, rec: &HitRecord<'_>, rng: &mut ThreadRng) -> Option<Scatter> {
-        let ratio = match rec.face {
-            Face::Front => self.idx.recip(),
-            Face::Back => self.idx,
+    fn scatter(&self, r: &Ray, rec: &HitRecord<'_>, rng: &mut dyn RngCore) -> Option<Scatter> {
+        let eta = self.eta(r, rec);
+
+        if self.distribution.is_smooth() {
+            let unit_direction = r.direction.unit();
+            let cos_theta = (-unit_direction).dot(rec.normal).min(1.0);
+            let reflectance = self.reflectance(r, rec, cos_theta, eta);
+            let p = Self::reflect_probability(reflectance);
+            let (direction, attenuation) = if p > CLOSED_OPEN_01.sample(rng) {
+                (unit_direction.reflect(rec.normal), reflectance / p)
+            } else {
+                (
+                    unit_direction.refract(rec.normal, eta.recip()),
+                    (Rgb::ONE - reflectance) / (1.0 - p),
+                )
+            };
+            let scattered = Ray::new(rec.point, direction, r.t);
+
+            return Some(Scatter::specular(
+                scattered,
+                attenuation * self.transmittance(r, rec),
+            ));
+        }
+
+        let uvw = rec.shading_frame();
+        let wo = uvw.to_local(-r.direction.unit());
+        if wo.z <= 0.0 {
+            return None;
+        }
+        let fresnel = |cos| self.reflectance(r, rec, cos, eta);
+        let wi = self.sample_with(eta, wo, fresnel, rng)?;
+        let (f, pdf) = self.evaluate_with(eta, wo, wi, fresnel);
+        if pdf <= 0.0 {
+            return None;
+        }
+
+        Some(Scatter::new(
+            Ray::new(rec.point, uvw.local(wi), r.t),
+            f / pdf * self.transmittance(r, rec),
+            pdf,
+        ))
+    }
+
+    fn attenuation(&self, r: &Ray, rec: &HitRecord<'_>, scattered: &Ray) -> Rgb {
+        if self.distribution.is_smooth() {
+            return Rgb::ZERO;
+        }
+        let (wo, wi) = Self::local(r, rec, scattered);
+        let eta = self.eta(r, rec);
+        let fresnel = |cos| self.reflectance(r, rec, cos, eta);
+
+        self.evaluate_with(eta, wo, wi, fresnel).0 * self.transmittance(r, rec)
+    }
+
+    fn scattering_pdf(&self, r: &Ray, rec: &HitRecord<'_>, scattered: &Ray) -> f64 {
+        if self.distribution.is_smooth() {
+            return 0.0;
+        }
+        let (wo, wi) = Self::local(r, rec, scattered);
+        let eta = self.eta(r, rec);
+        let fresnel = |cos| self.reflectance(r, rec, cos, eta);
+
+        self.evaluate_with(eta, wo, wi, fresnel).1
+    }
+
+    fn is_dispersive(&self) -> bool {
+        self.dispersion.is_some() || self.film.is_some()
+    }
+
+    fn is_diffuse(&self) -> bool {
+        false
+    }
+}
+
+/// [`Material`] that scatters light beneath its surface, like skin, wax, marble or milk, with a
+/// random walk through the [`Medium`] filling the object.
+///
+/// The surface reflects and refracts like a [`Dielectric`]. Each interaction inside the object
+/// counts as a bounce of the path.
+#[non_exhaustive]
+pub struct Subsurface {
+    /// The interface between the object and its surroundings.
+    pub boundary: Dielectric,
+    pub medium: Medium,
+}
+
+impl Subsurface {
+    /// A material with the refractive index `idx` that appears roughly the `color` once light has
+    /// scattered many times inside it, travelling the `mean_free_path` between interactions.
+    pub fn new(color: Rgb, mean_free_path: Rgb, idx: f64) -> Self {
+        Self {
+            boundary: Dielectric::new(idx),
+            medium: Medium::from_color(color, mean_free_path),
+        }
+    }
+
+    /// Roughen the surface with a perceptual `roughness` in [0, 1], which also lets lights that
+    /// rays cannot hit, like the sun, be sampled from beneath it.
+    #[must_use]
+    pub fn with_roughness(mut self, roughness: f64) -> Self {
+        self.boundary = self.boundary.with_roughness(roughness);
+        self
+    }
+}
+
+impl Material for Subsurface {
+    fn scatter(&self, r: &Ray, rec: &HitRecord<'_>, rng: &mut dyn RngCore) -> Option<Scatter> {
+        self.boundary.scatter(r, rec, rng)
+    }
+
+    fn attenuation(&self, r: &Ray, rec: &HitRecord<'_>, scattered: &Ray) -> Rgb {
+        self.boundary.attenuation(r, rec, scattered)
+    }
+
+    fn scattering_pdf(&self, r: &Ray, rec: &HitRecord<'_>, scattered: &Ray) -> f64 {
+        self.boundary.scattering_pdf(r, rec, scattered)
+    }
+
+    fn medium(&self) -> Option<&Medium> {
+        Some(&self.medium)
+    }
+
+    fn is_diffuse(&self) -> bool {
+        false
+    }
+}
+
+/// [`Material`] that blends two materials, scattering like `second` where the weight is 1 and like
+/// `first` where it is 0.
+#[non_exhaustive]
+pub struct Mix {
+    pub first: Arc<dyn Material>,
+    pub second: Arc<dyn Material>,
+    /// The weight of `second`, read from the red channel.
+    pub weight: Arc<dyn Texture>,
+}
+
+impl Mix {
+    pub const fn new(
+        first: Arc<dyn Material>,
+        second: Arc<dyn Material>,
+        weight: Arc<dyn Texture>,
+    ) -> Self {
+        Self {
+            first,
+            second,
+            weight,
+        }
+    }
+
+    pub fn from_weight(first: Arc<dyn Material>, second: Arc<dyn Material>, weight: f64) -> Self {
+        Self::new(first, second, Arc::new(Solid::new(Rgb::new_all(weight))))
+    }
+
+    fn weight(&self, rec: &HitRecord<'_>) -> f64 {
+        self.weight
+            .value(rec.u, rec.v, &rec.point)
+            .x
+            .clamp(0.0, 1.0)
+    }
+}
+
+impl Material for Mix {
+    fn scatter(&self, r: &Ray, rec: &HitRecord<'_>, rng: &mut dyn RngCore) -> Option<Scatter> {
+        let weight = self.weight(rec);
+        let (chosen, other, p) = if CLOSED_OPEN_01.sample(rng) < weight {
+            (&self.second, &self.first, weight)
+        } else {
+            (&self.first, &self.second, 1.0 - weight)
         };
-        let unit_direction = r.direction.unit();
-        let cos_theta = (-unit_direction).dot(rec.normal).min(1.0);
-        let sin_theta = cos_theta.mul_add(-cos_theta, 1.0).sqrt();
-        let direction = if ratio * sin_theta > 1.0
-            || Self::reflectance(cos_theta, ratio) > CLOSED_OPEN_01.sample(rng)
-        {
-            unit_direction.reflect(rec.normal)
+        let mut scatter = chosen.scatter(r, rec, rng)?;
+        // The weight of a specular lobe cancels out with the probability of choosing it.
+        if scatter.specular {
+            return Some(scatter);
+        }
+
+        // Either material could have scattered along the ray, so weigh it by both.
+        let f = scatter.attenuation.mul_add(
+            p * scatter.pdf,
+            (1.0 - p) * other.attenuation(r, rec, &scatter.ray),
+        );
+        let pdf = p.mul_add(
+            scatter.pdf,
+            (1.0 - p) * other.scattering_pdf(r, rec, &scatter.ray),
+        );
+        scatter.attenuation = f / pdf;
+        scatter.pdf = pdf;
+
+        Some(scatter)
+    }
+
+    fn attenuation(&self, r: &Ray, rec: &HitRecord<'_>, scattered: &Ray) -> Rgb {
+        let weight = self.weight(rec);
+        self.first.attenuation(r, rec, scattered).mul_add(
+            1.0 - weight,
+            weight * self.second.attenuation(r, rec, scattered),
+        )
+    }
+
+    fn scattering_pdf(&self, r: &Ray, rec: &HitRecord<'_>, scattered: &Ray) -> f64 {
+        let weight = self.weight(rec);
+        (1.0 - weight).mul_add(
+            self.first.scattering_pdf(r, rec, scattered),
+            weight * self.second.scattering_pdf(r, rec, scattered),
+        )
+    }
+
+    fn emitted(&self, r: &Ray, rec: &HitRecord<'_>) -> Rgb {
+        let weight = self.weight(rec);
+        self.first
+            .emitted(r, rec)
+            .mul_add(1.0 - weight, weight * self.second.emitted(r, rec))
+    }
+
+    fn is_dispersive(&self) -> bool {
+        self.first.is_dispersive() || self.second.is_dispersive()
+    }
+
+    fn is_diffuse(&self) -> bool {
+        self.first.is_diffuse() || self.second.is_diffuse()
+    }
+}
+
+/// [`Material`] that coats a base material in a clear dielectric layer, like varnish or lacquer.
+///
+/// Light reflects off the coat by its Fresnel reflectance, and the rest reaches the base and must
+/// cross the coat again on its way out.
+#[non_exhaustive]
+pub struct Coated {
+    pub base: Arc<dyn Material>,
+    /// The refractive index of the coat.
+    pub idx: f64,
+    pub distribution: TrowbridgeReitz,
+}
+
+impl Coated {
+    pub const fn new(base: Arc<dyn Material>, idx: f64) -> Self {
+        Self {
+            base,
+            idx,
+            distribution: TrowbridgeReitz::new(0.0, 0.0),
+        }
+    }
+
+    /// Roughen the coat with a perceptual `roughness` in [0, 1].
+    #[must_use]
+    pub fn with_roughness(mut self, roughness: f64) -> Self {
+        self.distribution = TrowbridgeReitz::from_roughness(roughness, roughness);
+        self
+    }
+
+    /// The BSDF times the cosine of the direction of `scattered`, and the density of sampling it.
+    fn evaluate(&self, r: &Ray, rec: &HitRecord<'_>, scattered: &Ray) -> (Rgb, f64) {
+        let uvw = rec.shading_frame();
+        let wo = uvw.to_local(-r.direction.unit());
+        let wi = uvw.to_local(scattered.direction.unit());
+        if wo.z <= 0.0 {
+            return (Rgb::ZERO, 0.0);
+        }
+        let reflectance = fresnel_dielectric(wo.z, self.idx);
+        let transmittance = 1.0 - fresnel_dielectric(wi.z.abs(), self.idx);
+
+        let mut f = (1.0 - reflectance) * transmittance * self.base.attenuation(r, rec, scattered);
+        let mut pdf = (1.0 - reflectance) * self.base.scattering_pdf(r, rec, scattered);
+
+        if !self.distribution.is_smooth() && wi.z > 0.0 {
+            let wm = (wo + wi).unit();
+            let cos = wo.dot(wm);
+            if cos > 0.0 {
+                let dist = self.distribution;
+                let coat = dist.d(wm) * dist.g(wo, wi) * fresnel_dielectric(cos, self.idx);
+                f += Rgb::new_all(coat / (4.0 * wo.z));
+                pdf = reflectance.mul_add(dist.d_visible(wo, wm) / (4.0 * cos), pdf);
+            }
+        }
+
+        (f, pdf)
+    }
+}
+
+impl Material for Coated {
+    fn scatter(&self, r: &Ray, rec: &HitRecord<'_>, rng: &mut dyn RngCore) -> Option<Scatter> {
+        // The coat is only on the outside of the surface.
+        if rec.face == Face::Back {
+            return self.base.scatter(r, rec, rng);
+        }
+        let uvw = rec.shading_frame();
+        let wo = uvw.to_local(-r.direction.unit());
+        if wo.z <= 0.0 {
+            return None;
+        }
+
+        let scattered = if CLOSED_OPEN_01.sample(rng) < fresnel_dielectric(wo.z, self.idx) {
+            if self.distribution.is_smooth() {
+                let wi = Vec3::newf(-wo.x, -wo.y, wo.z);
+                let scattered = Ray::new(rec.point, uvw.local(wi), r.t);
+                return Some(Scatter::specular(scattered, Rgb::ONE));
+            }
+            let wi = (-wo).reflect(self.distribution.sample_visible(wo, rng));
+            Ray::new(rec.point, uvw.local(wi), r.t)
         } else {
-            unit_direction.refract(rec.normal, ratio)
+            let mut scatter = self.base.scatter(r, rec, rng)?;
+            if scatter.specular {
+                let cos = scatter.ray.direction.unit().dot(rec.normal).abs();
+                scatter.attenuation *= 1.0 - fresnel_dielectric(cos, self.idx);
+                return Some(scatter);
+            }
+            scatter.ray
         };
-        let scattered = Ray::new(rec.point, direction, r.t);
 
-        Some(Scatter::new(scattered, Rgb::ONE))
+        let (f, pdf) = self.evaluate(r, rec, &scattered);
+        if pdf <= 0.0 {
+            return None;
+        }
+
+        Some(Scatter::new(scattered, f / pdf, pdf))
+    }
+
+    fn attenuation(&self, r: &Ray, rec: &HitRecord<'_>, scattered: &Ray) -> Rgb {
+        match rec.face {
+            Face::Front => self.evaluate(r, rec, scattered).0,
+            Face::Back => self.base.attenuation(r, rec, scattered),
+        }
+    }
+
+    fn scattering_pdf(&self, r: &Ray, rec: &HitRecord<'_>, scattered: &Ray) -> f64 {
+        match rec.face {
+            Face::Front => self.evaluate(r, rec, scattered).1,
+            Face::Back => self.base.scattering_pdf(r, rec, scattered),
+        }
+    }
+
+    fn emitted(&self, r: &Ray, rec: &HitRecord<'_>) -> Rgb {
+        self.base.emitted(r, rec)
+    }
+
+    fn is_dispersive(&self) -> bool {
+        self.base.is_dispersive()
+    }
+
+    fn medium(&self) -> Option<&Medium> {
+        self.base.medium()
+    }
+
+    fn is_diffuse(&self) -> bool {
+        self.base.is_diffuse()
+    }
+}
+
+/// [`Material`] that emits light from the front of its surface and scatters none.
+#[non_exhaustive]
+pub struct DiffuseLight {
+    /// The emitted color, which may be brighter than 1.
+    pub emit: Arc<dyn Texture>,
+}
+
+impl DiffuseLight {
+    pub const fn new(emit: Arc<dyn Texture>) -> Self {
+        Self { emit }
+    }
+}
+
+impl Material for DiffuseLight {
+    fn scatter(&self, _r: &Ray, _rec: &HitRecord<'_>, _rng: &mut dyn RngCore) -> Option<Scatter> {
+        None
+    }
+
+    fn emitted(&self, _r: &Ray, rec: &HitRecord<'_>) -> Rgb {
+        match rec.face {
+            Face::Front => self.emit.value(rec.u, rec.v, &rec.point),
+            Face::Back => Rgb::ZERO,
+        }
+    }
+}
+
+/// [`Material`] that cuts a hole through the image to the background where the camera sees it,
+/// for objects in front of the scene in the background. Elsewhere, it is black.
+pub struct Holdout;
+
+impl Material for Holdout {
+    fn scatter(&self, _r: &Ray, _rec: &HitRecord<'_>, _rng: &mut dyn RngCore) -> Option<Scatter> {
+        None
+    }
+
+    fn matte(&self) -> Option<Matte> {
+        Some(Matte::Holdout)
+    }
+}
+
+/// [`Material`] that shows only the shadows and reflections it receives where the camera sees
+/// it, for surfaces in the background that the scene stands on. Elsewhere, it is [`Lambertian`].
+#[non_exhaustive]
+pub struct ShadowCatcher {
+    pub surface: Lambertian,
+}
+
+impl ShadowCatcher {
+    pub const fn new(albedo: Arc<dyn Texture>) -> Self {
+        Self {
+            surface: Lambertian::new(albedo),
+        }
+    }
+}
+
+impl Material for ShadowCatcher {
+    fn scatter(&self, r: &Ray, rec: &HitRecord<'_>, rng: &mut dyn RngCore) -> Option<Scatter> {
+        self.surface.scatter(r, rec, rng)
+    }
+
+    fn attenuation(&self, r: &Ray, rec: &HitRecord<'_>, scattered: &Ray) -> Rgb {
+        self.surface.attenuation(r, rec, scattered)
+    }
+
+    fn scattering_pdf(&self, r: &Ray, rec: &HitRecord<'_>, scattered: &Ray) -> f64 {
+        self.surface.scattering_pdf(r, rec, scattered)
+    }
+
+    fn matte(&self) -> Option<Matte> {
+        Some(Matte::ShadowCatcher)
     }
 }
@@ -0,0 +1,115 @@
+use rand::prelude::*;
+
+use crate::{
+    graphics::Ray,
+    math::{Rgb, Vec3},
+    rng::CLOSED_OPEN_01,
+    spectrum::Wavelengths,
+};
+
+/// A homogeneous medium filling an object, in which light travels in straight lines between
+/// interactions that scatter it in a uniformly random direction or absorb it.
+#[derive(Clone, Copy)]
+#[non_exhaustive]
+pub struct Medium {
+    /// The average distance light travels between interactions, per color channel.
+    pub mean_free_path: Rgb,
+    /// The fraction of light scattered rather than absorbed by each interaction, per color channel.
+    pub albedo: Rgb,
+}
+
+/// Where light travelling through a [`Medium`] first interacted with it.
+pub enum Flight {
+    /// The light was scattered along `ray`, and the light arriving along it is multiplied by
+    /// `weight`.
+    Scattered { ray: Ray, weight: Vec3 },
+    /// The light crossed the medium without an interaction, and is multiplied by `weight`.
+    Crossed { weight: Vec3 },
+}
+
+impl Medium {
+    pub const fn new(mean_free_path: Rgb, albedo: Rgb) -> Self {
+        Self {
+            mean_free_path,
+            albedo,
+        }
+    }
+
+    /// A medium that appears roughly the `color` once light has scattered many times inside it,
+    /// which is converted to the albedo of each interaction with the fit of Chiang, Kutz and
+    /// Burley.
+    pub fn from_color(color: Rgb, mean_free_path: Rgb) -> Self {
+        let albedo = |a: f64| {
+            let a = a.clamp(0.0, 1.0);
+            let t = 4.20863f64.mul_add(a, 4.09712)
+                - 17.7126f64
+                    .mul_add(a * a, 41.6808f64.mul_add(a, 9.59217))
+                    .sqrt();
+            t.mul_add(-t, 1.0)
+        };
+
+        Self::new(
+            mean_free_path,
+            Rgb::newf(albedo(color.x), albedo(color.y), albedo(color.z)),
+        )
+    }
+
+    /// Sample where light travelling `distance` along `r` through the medium first interacts with
+    /// it, for each of the `wavelengths`.
+    ///
+    /// The distance is sampled for one wavelength, picked in proportion to the `throughput` of the
+    /// path so far, which keeps paths that mostly carry one color from being weighted by the
+    /// others.
+    pub fn sample(
+        &self,
+        r: &Ray,
+        distance: f64,
+        wavelengths: Wavelengths,
+        throughput: Vec3,
+        rng: &mut dyn RngCore,
+    ) -> Flight {
+        let sigma_t = Vec3::ONE / wavelengths.interpolate(self.mean_free_path);
+        let transmittance = |d: f64| {
+            Vec3::newf(
+                (-sigma_t.x * d).exp(),
+                (-sigma_t.y * d).exp(),
+                (-sigma_t.z * d).exp(),
+            )
+        };
+        let total = throughput.x + throughput.y + throughput.z;
+        let probabilities = if total > 0.0 {
+            throughput / total
+        } else {
+            Vec3::new_all(1.0 / 3.0)
+        };
+
+        let u = CLOSED_OPEN_01.sample(rng);
+        let sigma = if u < probabilities.x {
+            sigma_t.x
+        } else if u < probabilities.x + probabilities.y {
+            sigma_t.y
+        } else {
+            sigma_t.z
+        };
+        let sampled = -(1.0 - CLOSED_OPEN_01.sample(rng)).ln() / sigma;
+
+        if sampled >= distance {
+            let transmittance = transmittance(distance);
+            return Flight::Crossed {
+                weight: transmittance / probabilities.dot(transmittance),
+            };
+        }
+
+        let density = sigma_t * transmittance(sampled);
+        let ray = Ray::new(
+            r.at(sampled / r.direction.len()),
+            Vec3::random_unit_vec(rng),
+            r.t,
+        );
+
+        Flight::Scattered {
+            ray,
+            weight: wavelengths.uplift(self.albedo) * density / probabilities.dot(density),
+        }
+    }
+}
@@ -0,0 +1,172 @@
+use std::f64::consts::PI;
+
+use rand::prelude::*;
+
+use crate::{math::Vec3, rng::CLOSED_OPEN_01};
+
+/// The Trowbridge-Reitz (GGX) distribution of microfacet normals, with Smith masking-shadowing.
+///
+/// Directions are in a local frame with the macrosurface normal along +z, and the roughness may
+/// differ along x and y for anisotropic surfaces.
+#[derive(Clone, Copy)]
+pub struct TrowbridgeReitz {
+    alpha_x: f64,
+    alpha_y: f64,
+}
+
+impl TrowbridgeReitz {
+    /// Below this roughness the distribution is treated as a perfect mirror.
+    const SMOOTH: f64 = 1.0e-3;
+
+    pub const fn new(alpha_x: f64, alpha_y: f64) -> Self {
+        Self { alpha_x, alpha_y }
+    }
+
+    /// A distribution from perceptual roughnesses in [0, 1], whose squares are the slopes' alphas.
+    pub fn from_roughness(roughness_x: f64, roughness_y: f64) -> Self {
+        Self::new(
+            roughness_x.clamp(0.0, 1.0).powi(2),
+            roughness_y.clamp(0.0, 1.0).powi(2),
+        )
+    }
+
+    /// Whether the surface is smooth enough to be treated as perfectly specular.
+    pub const fn is_smooth(self) -> bool {
+        self.alpha_x.max(self.alpha_y) < Self::SMOOTH
+    }
+
+    /// The density of microfacets with the normal `wm`, per unit area of the macrosurface.
+    pub fn d(self, wm: Vec3) -> f64 {
+        if wm.z <= 0.0 {
+            return 0.0;
+        }
+        let x = wm.x / self.alpha_x;
+        let y = wm.y / self.alpha_y;
+        let e = x.mul_add(x, y.mul_add(y, wm.z * wm.z));
+
+        (PI * self.alpha_x * self.alpha_y * e * e).recip()
+    }
+
+    /// Smith's auxiliary function, the ratio of microfacet area hidden from `w` to visible area.
+    fn lambda(self, w: Vec3) -> f64 {
+        let x = w.x * self.alpha_x;
+        let y = w.y * self.alpha_y;
+        let alpha2_tan2 = x.mul_add(x, y * y) / (w.z * w.z);
+        if !alpha2_tan2.is_finite() {
+            return 0.0;
+        }
+
+        0.5 * ((1.0 + alpha2_tan2).sqrt() - 1.0)
+    }
+
+    /// The fraction of microfacets visible from `w`.
+    pub fn g1(self, w: Vec3) -> f64 {
+        (1.0 + self.lambda(w)).recip()
+    }
+
+    /// The fraction of microfacets visible from both `wo` and `wi`.
+    pub fn g(self, wo: Vec3, wi: Vec3) -> f64 {
+        (1.0 + self.lambda(wo) + self.lambda(wi)).recip()
+    }
+
+    /// The density of microfacet normals `wm` visible from `w`.
+    pub fn d_visible(self, w: Vec3, wm: Vec3) -> f64 {
+        if w.z == 0.0 {
+            return 0.0;
+        }
+
+        self.g1(w) / w.z.abs() * self.d(wm) * w.dot(wm).abs()
+    }
+
+    /// A microfacet normal sampled from those visible from `w`, with the density
+    /// [`TrowbridgeReitz::d_visible`].
+    pub fn sample_visible(self, w: Vec3, rng: &mut dyn RngCore) -> Vec3 {
+        // Stretch `w` to the hemisphere configuration, where the slopes are unit.
+        let mut wh = Vec3::newf(self.alpha_x * w.x, self.alpha_y * w.y, w.z).unit();
+        if wh.z < 0.0 {
+            wh = -wh;
+        }
+        let t1 = if wh.z < 0.99999 {
+            Vec3::newi(0, 0, 1).cross(wh).unit()
+        } else {
+            Vec3::newi(1, 0, 0)
+        };
+        let t2 = wh.cross(t1);
+
+        // A uniform point on the disc, warped to the projection of the visible hemisphere.
+        let r = CLOSED_OPEN_01.sample(rng).sqrt();
+        let (sin, cos) = (2.0 * PI * CLOSED_OPEN_01.sample(rng)).sin_cos();
+        let px = r * cos;
+        let h = px.mul_add(-px, 1.0).sqrt();
+        let s = 0.5 * (1.0 + wh.z);
+        let py = (1.0 - s).mul_add(h, s * r * sin);
+        let pz = px.mul_add(-px, py.mul_add(-py, 1.0)).max(0.0).sqrt();
+        let nh = t1.mul_add(px, t2.mul_add(py, pz * wh));
+
+        // Unstretch the normal back to the ellipsoid.
+        Vec3::newf(self.alpha_x * nh.x, self.alpha_y * nh.y, nh.z.max(1.0e-6)).unit()
+    }
+}
+
+/// The Fresnel reflectance of a conductor with the complex refractive index `eta` + *i*`k`, for
+/// light arriving at the cosine `cos` from the normal.
+pub fn fresnel_conductor(cos: f64, eta: f64, k: f64) -> f64 {
+    let cos = cos.clamp(0.0, 1.0);
+    let cos2 = cos * cos;
+    let sin2 = 1.0 - cos2;
+    let eta2 = eta * eta;
+    let k2 = k * k;
+
+    let t0 = eta2 - k2 - sin2;
+    let a2_plus_b2 = t0.mul_add(t0, 4.0 * eta2 * k2).sqrt();
+    let t1 = a2_plus_b2 + cos2;
+    let a = (0.5 * (a2_plus_b2 + t0)).max(0.0).sqrt();
+    let t2 = 2.0 * cos * a;
+    let rs = (t1 - t2) / (t1 + t2);
+    let t3 = cos2.mul_add(a2_plus_b2, sin2 * sin2);
+    let t4 = t2 * sin2;
+    let rp = rs * (t3 - t4) / (t3 + t4);
+
+    0.5 * (rp + rs)
+}
+
+/// The Fresnel reflectance of an interface between dielectrics, for light arriving at the cosine
+/// `cos` from the normal.
+///
+/// `eta` is the ratio of the refractive index behind the normal to that in front of it, and light
+/// arriving from behind has a negative `cos`.
+pub fn fresnel_dielectric(cos: f64, eta: f64) -> f64 {
+    let (cos, eta) = if cos < 0.0 {
+        (-cos.max(-1.0), eta.recip())
+    } else {
+        (cos.min(1.0), eta)
+    };
+    let sin2_t = cos.mul_add(-cos, 1.0) / (eta * eta);
+    if sin2_t >= 1.0 {
+        // Total internal reflection.
+        return 1.0;
+    }
+    let cos_t = (1.0 - sin2_t).sqrt();
+
+    let r_parallel = eta.mul_add(cos, -cos_t) / eta.mul_add(cos, cos_t);
+    let r_perpendicular = eta.mul_add(-cos_t, cos) / eta.mul_add(cos_t, cos);
+
+    0.5 * r_parallel.mul_add(r_parallel, r_perpendicular * r_perpendicular)
+}
+
+/// The unit direction of light arriving from `w` after refracting through a surface with the
+/// normal `n`, on the same side as `w`.
+///
+/// The medium behind the surface has a refractive index `eta` times that of the medium in front.
+/// None for total internal reflection.
+pub fn refract(w: Vec3, n: Vec3, eta: f64) -> Option<Vec3> {
+    let cos_i = w.dot(n);
+    let sin2_t = cos_i.mul_add(-cos_i, 1.0).max(0.0) / (eta * eta);
+    if sin2_t >= 1.0 {
+        return None;
+    }
+    let cos_t = (1.0 - sin2_t).sqrt();
+
+    // -w / eta + (cos_i / eta - cos_t) * n
+    Some(n.mul_add(cos_i / eta - cos_t, -w / eta))
+}
@@ -1,17 +1,34 @@
 //! Graphics traits and types.
 
 mod aabb;
+mod bump;
 mod bvh;
 mod hit;
+mod kdtree;
 mod material;
+mod medium;
+mod microfacet;
+mod pdf;
 mod perlin;
+mod principled;
 mod ray;
 mod texture;
+mod thin_film;
 
 pub use aabb::Aabb;
+pub use bump::{Bumped, Perturbation};
 pub use bvh::Bvh;
 pub use hit::{Face, Hit, HitList, HitRecord};
-pub use material::{Dielectric, Lambertian, Material, Metallic};
+pub use kdtree::KdTree;
+pub use material::{
+    Coated, Conductor, Dielectric, DiffuseLight, Holdout, Lambertian, Material, Matte, Metallic,
+    Mix, Scatter, ShadowCatcher, Subsurface,
+};
+pub use medium::{Flight, Medium};
+pub use microfacet::{TrowbridgeReitz, fresnel_conductor, fresnel_dielectric, refract};
+pub use pdf::{CosinePdf, HitPdf, MixturePdf, Pdf, SpherePdf, power_heuristic};
 pub use perlin::Perlin;
+pub use principled::Principled;
 pub use ray::Ray;
-pub use texture::{Checkered, Noise, Solid, Texture};
+pub use texture::{Checkered, Image, Noise, Solid, Texture};
+pub use thin_film::ThinFilm;
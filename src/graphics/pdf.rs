@@ -0,0 +1,111 @@
+use std::f64::consts::PI;
+
+use rand::prelude::*;
+
+use crate::{
+    graphics::Hit,
+    math::{Onb, Point, Vec3},
+    rng::CLOSED_OPEN_01,
+};
+
+/// A probability density function over directions.
+pub trait Pdf {
+    /// The probability density of sampling `direction`.
+    fn value(&self, direction: Vec3) -> f64;
+    /// Sample a direction from the distribution.
+    fn generate(&self, rng: &mut dyn RngCore) -> Vec3;
+}
+
+/// Directions uniformly distributed over the unit sphere.
+pub struct SpherePdf;
+
+impl Pdf for SpherePdf {
+    fn value(&self, _direction: Vec3) -> f64 {
+        (4.0 * PI).recip()
+    }
+
+    fn generate(&self, rng: &mut dyn RngCore) -> Vec3 {
+        Vec3::random_unit_vec(rng)
+    }
+}
+
+/// Directions in the hemisphere around a normal, with a density proportional to the cosine of
+/// their angle from the normal.
+pub struct CosinePdf {
+    uvw: Onb,
+}
+
+impl CosinePdf {
+    pub fn new(normal: Vec3) -> Self {
+        Self {
+            uvw: Onb::from_w(normal),
+        }
+    }
+}
+
+impl Pdf for CosinePdf {
+    fn value(&self, direction: Vec3) -> f64 {
+        let cos_theta = direction.unit().dot(self.uvw.w);
+        cos_theta.max(0.0) / PI
+    }
+
+    fn generate(&self, rng: &mut dyn RngCore) -> Vec3 {
+        self.uvw.local(Vec3::random_cosine_direction(rng))
+    }
+}
+
+/// Directions from an origin towards an object that implements [`Hit`].
+pub struct HitPdf<'a> {
+    object: &'a dyn Hit,
+    origin: Point,
+}
+
+impl<'a> HitPdf<'a> {
+    pub const fn new(object: &'a dyn Hit, origin: Point) -> Self {
+        Self { object, origin }
+    }
+}
+
+impl Pdf for HitPdf<'_> {
+    fn value(&self, direction: Vec3) -> f64 {
+        self.object.pdf_value(self.origin, direction)
+    }
+
+    fn generate(&self, rng: &mut dyn RngCore) -> Vec3 {
+        self.object.random(self.origin, rng)
+    }
+}
+
+/// An equally weighted mixture of two [`Pdf`]s.
+pub struct MixturePdf<'a> {
+    pdfs: [&'a dyn Pdf; 2],
+}
+
+impl<'a> MixturePdf<'a> {
+    pub const fn new(a: &'a dyn Pdf, b: &'a dyn Pdf) -> Self {
+        Self { pdfs: [a, b] }
+    }
+}
+
+impl Pdf for MixturePdf<'_> {
+    fn value(&self, direction: Vec3) -> f64 {
+        0.5 * (self.pdfs[0].value(direction) + self.pdfs[1].value(direction))
+    }
+
+    fn generate(&self, rng: &mut dyn RngCore) -> Vec3 {
+        if CLOSED_OPEN_01.sample(rng) < 0.5 {
+            self.pdfs[0].generate(rng)
+        } else {
+            self.pdfs[1].generate(rng)
+        }
+    }
+}
+
+/// The power heuristic weight, with an exponent of 2, for a sample drawn from a strategy with
+/// density `f_pdf` combined with another strategy with density `g_pdf`.
+pub fn power_heuristic(f_pdf: f64, g_pdf: f64) -> f64 {
+    let f = f_pdf * f_pdf;
+    let g = g_pdf * g_pdf;
+
+    if f + g > 0.0 { f / (f + g) } else { 0.0 }
+}
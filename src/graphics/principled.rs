@@ -0,0 +1,336 @@
+use std::{f64::consts::PI, sync::Arc};
+
+use rand::prelude::*;
+
+use crate::{
+    graphics::{
+        Dielectric, Face, HitRecord, Material, Ray, Scatter, Solid, Texture, TrowbridgeReitz,
+    },
+    math::{Rgb, Vec3},
+    rng::CLOSED_OPEN_01,
+};
+
+/// [`Material`] following the Disney principled BSDF, which layers a diffuse base, sheen,
+/// specular reflection, rough transmission and a clear coat, blended by artist-friendly
+/// parameters.
+///
+/// Every parameter is a [`Texture`]; those that are scalars read the red channel, and all but the
+/// refractive index are in [0, 1].
+#[non_exhaustive]
+pub struct Principled {
+    pub base_color: Arc<dyn Texture>,
+    /// Blends from a dielectric to a metal tinted by the base color.
+    pub metallic: Arc<dyn Texture>,
+    /// The perceptual roughness of the specular and transmission lobes.
+    pub roughness: Arc<dyn Texture>,
+    /// The strength of specular reflection off dielectrics, where 0.5 reflects 4% at normal
+    /// incidence.
+    pub specular: Arc<dyn Texture>,
+    /// Tints dielectric specular reflection towards the base color.
+    pub specular_tint: Arc<dyn Texture>,
+    /// A soft retroreflective rim for cloth.
+    pub sheen: Arc<dyn Texture>,
+    /// Tints the sheen towards the base color.
+    pub sheen_tint: Arc<dyn Texture>,
+    /// The strength of a second, colorless specular lobe over everything else.
+    pub clearcoat: Arc<dyn Texture>,
+    /// The glossiness of the clear coat, from satin to gloss.
+    pub clearcoat_gloss: Arc<dyn Texture>,
+    /// Blends from an opaque dielectric to rough glass that tints light passing through it by the
+    /// base color.
+    pub transmission: Arc<dyn Texture>,
+    /// The refractive index of transmission.
+    pub ior: Arc<dyn Texture>,
+}
+
+/// The parameters of a [`Principled`] material at a point.
+struct Parameters {
+    base_color: Rgb,
+    metallic: f64,
+    roughness: f64,
+    specular: Rgb,
+    sheen: Rgb,
+    clearcoat: f64,
+    clearcoat_alpha: f64,
+    transmission: f64,
+    distribution: TrowbridgeReitz,
+    glass: Dielectric,
+    eta: f64,
+}
+
+impl Parameters {
+    /// The probabilities of sampling the diffuse, specular, transmission and clear coat lobes for
+    /// light leaving along `wo`.
+    fn lobes(&self, wo: Vec3) -> [f64; 4] {
+        let diffuse = (1.0 - self.metallic) * (1.0 - self.transmission);
+        let transmission = (1.0 - self.metallic) * self.transmission;
+        let weights = [
+            diffuse * self.base_color.luminance().max(0.01),
+            (1.0 - transmission) * schlick(self.specular, wo.z).luminance(),
+            transmission,
+            0.25 * self.clearcoat * schlick(Rgb::new_all(0.04), wo.z).x,
+        ];
+        let total: f64 = weights.iter().sum();
+
+        weights.map(|w| w / total)
+    }
+}
+
+impl Principled {
+    /// A rough white dielectric with the given base color.
+    pub fn new(base_color: Arc<dyn Texture>) -> Self {
+        let constant = |n: f64| -> Arc<dyn Texture> { Arc::new(Solid::new(Rgb::new_all(n))) };
+
+        Self {
+            base_color,
+            metallic: constant(0.0),
+            roughness: constant(0.5),
+            specular: constant(0.5),
+            specular_tint: constant(0.0),
+            sheen: constant(0.0),
+            sheen_tint: constant(0.5),
+            clearcoat: constant(0.0),
+            clearcoat_gloss: constant(1.0),
+            transmission: constant(0.0),
+            ior: constant(1.5),
+        }
+    }
+
+    #[must_use]
+    pub fn with_metallic(mut self, metallic: Arc<dyn Texture>) -> Self {
+        self.metallic = metallic;
+        self
+    }
+
+    #[must_use]
+    pub fn with_roughness(mut self, roughness: Arc<dyn Texture>) -> Self {
+        self.roughness = roughness;
+        self
+    }
+
+    #[must_use]
+    pub fn with_specular(mut self, specular: Arc<dyn Texture>, tint: Arc<dyn Texture>) -> Self {
+        self.specular = specular;
+        self.specular_tint = tint;
+        self
+    }
+
+    #[must_use]
+    pub fn with_sheen(mut self, sheen: Arc<dyn Texture>, tint: Arc<dyn Texture>) -> Self {
+        self.sheen = sheen;
+        self.sheen_tint = tint;
+        self
+    }
+
+    #[must_use]
+    pub fn with_clearcoat(mut self, clearcoat: Arc<dyn Texture>, gloss: Arc<dyn Texture>) -> Self {
+        self.clearcoat = clearcoat;
+        self.clearcoat_gloss = gloss;
+        self
+    }
+
+    #[must_use]
+    pub fn with_transmission(
+        mut self,
+        transmission: Arc<dyn Texture>,
+        ior: Arc<dyn Texture>,
+    ) -> Self {
+        self.transmission = transmission;
+        self.ior = ior;
+        self
+    }
+
+    fn parameters(&self, rec: &HitRecord<'_>) -> Parameters {
+        let value = |texture: &Arc<dyn Texture>| texture.value(rec.u, rec.v, &rec.point);
+        let scalar = |texture: &Arc<dyn Texture>| value(texture).x.clamp(0.0, 1.0);
+
+        let base_color = value(&self.base_color);
+        let metallic = scalar(&self.metallic);
+        let roughness = scalar(&self.roughness);
+        let ior = value(&self.ior).x.max(1.0);
+
+        // The hue of the base color, without its luminance.
+        let luminance = base_color.luminance();
+        let tint = if luminance > 0.0 {
+            base_color / luminance
+        } else {
+            Rgb::ONE
+        };
+        let lerp = |a: Rgb, b: Rgb, t: f64| a + t * (b - a);
+        let specular =
+            lerp(Rgb::ONE, tint, scalar(&self.specular_tint)) * 0.08 * scalar(&self.specular);
+
+        // Clamp the roughness so the specular lobes stay finite.
+        let alpha = roughness.powi(2).max(1.0e-3);
+        let distribution = TrowbridgeReitz::new(alpha, alpha);
+        let mut glass = Dielectric::new(ior);
+        glass.distribution = distribution;
+
+        Parameters {
+            base_color,
+            metallic,
+            roughness,
+            specular: lerp(specular, base_color, metallic),
+            sheen: scalar(&self.sheen) * lerp(Rgb::ONE, tint, scalar(&self.sheen_tint)),
+            clearcoat: scalar(&self.clearcoat),
+            clearcoat_alpha: scalar(&self.clearcoat_gloss).mul_add(0.001 - 0.1, 0.1),
+            transmission: scalar(&self.transmission),
+            distribution,
+            glass,
+            eta: match rec.face {
+                Face::Front => ior,
+                Face::Back => ior.recip(),
+            },
+        }
+    }
+
+    /// The BSDF times the cosine of `wi`, and the density of sampling `wi`, in the shading frame
+    /// where `wo` is above the surface.
+    fn evaluate(p: &Parameters, wo: Vec3, wi: Vec3) -> (Rgb, f64) {
+        let [p_diffuse, p_specular, p_transmission, p_clearcoat] = p.lobes(wo);
+        let mut f = Rgb::ZERO;
+        let mut pdf = 0.0;
+
+        let transmission = (1.0 - p.metallic) * p.transmission;
+        if p_transmission > 0.0 {
+            let (glass, glass_pdf) = p.glass.evaluate(p.eta, wo, wi);
+            // Light is tinted going into an object and again coming out, so each refraction
+            // takes the square root of the base color to tint a pass through it by the base color.
+            let color = if wi.z < 0.0 {
+                Rgb::newf(
+                    p.base_color.x.sqrt(),
+                    p.base_color.y.sqrt(),
+                    p.base_color.z.sqrt(),
+                )
+            } else {
+                Rgb::ONE
+            };
+            f += transmission * glass * color;
+            pdf = p_transmission.mul_add(glass_pdf, pdf);
+        }
+
+        if wi.z <= 0.0 {
+            return (f, pdf);
+        }
+        let wm = (wo + wi).unit();
+        let cos_d = wi.dot(wm);
+
+        // Burley's diffuse, with retroreflection at grazing angles on rough surfaces, and sheen.
+        let diffuse = (1.0 - p.metallic) * (1.0 - p.transmission);
+        if diffuse > 0.0 {
+            let fd90 = (2.0 * p.roughness).mul_add(cos_d * cos_d, 0.5);
+            let fd = |cos: f64| (fd90 - 1.0).mul_add((1.0 - cos).powi(5), 1.0);
+            let retro = fd(wo.z) * fd(wi.z) / PI;
+            let sheen = (1.0 - cos_d).powi(5) * p.sheen;
+            f += diffuse * wi.z * (retro * p.base_color + sheen);
+            pdf += p_diffuse * wi.z / PI;
+        }
+
+        if cos_d > 0.0 {
+            let dist = p.distribution;
+            let d = dist.d(wm);
+            let specular = d * dist.g(wo, wi) / (4.0 * wo.z) * schlick(p.specular, cos_d);
+            f += (1.0 - transmission) * specular;
+            pdf += p_specular * dist.d_visible(wo, wm) / (4.0 * cos_d);
+
+            if p.clearcoat > 0.0 {
+                let d = gtr1(wm.z, p.clearcoat_alpha);
+                let g = TrowbridgeReitz::new(0.25, 0.25).g(wo, wi);
+                let fresnel = schlick(Rgb::new_all(0.04), cos_d).x;
+                f += Rgb::new_all(0.25 * p.clearcoat * d * g * fresnel / (4.0 * wo.z));
+                pdf += p_clearcoat * d * wm.z / (4.0 * cos_d);
+            }
+        }
+
+        (f, pdf)
+    }
+
+    fn local(r: &Ray, rec: &HitRecord<'_>, scattered: &Ray) -> (Vec3, Vec3) {
+        let uvw = rec.shading_frame();
+        (
+            uvw.to_local(-r.direction.unit()),
+            uvw.to_local(scattered.direction.unit()),
+        )
+    }
+}
+
+impl Material for Principled {
+    fn scatter(&self, r: &Ray, rec: &HitRecord<'_>, rng: &mut dyn RngCore) -> Option<Scatter> {
+        let uvw = rec.shading_frame();
+        let wo = uvw.to_local(-r.direction.unit());
+        if wo.z <= 0.0 {
+            return None;
+        }
+        let p = self.parameters(rec);
+
+        // Pick a lobe to sample, then weigh the direction by the density of every lobe.
+        let [p_diffuse, p_specular, p_transmission, _] = p.lobes(wo);
+        let choice = CLOSED_OPEN_01.sample(rng);
+        let wi = if choice < p_diffuse {
+            Vec3::random_cosine_direction(rng)
+        } else if choice < p_diffuse + p_specular {
+            (-wo).reflect(p.distribution.sample_visible(wo, rng))
+        } else if choice < p_diffuse + p_specular + p_transmission {
+            p.glass.sample(p.eta, wo, rng)?
+        } else {
+            (-wo).reflect(sample_gtr1(p.clearcoat_alpha, rng))
+        };
+
+        let (f, pdf) = Self::evaluate(&p, wo, wi);
+        if pdf <= 0.0 {
+            return None;
+        }
+
+        Some(Scatter::new(
+            Ray::new(rec.point, uvw.local(wi), r.t),
+            f / pdf,
+            pdf,
+        ))
+    }
+
+    fn attenuation(&self, r: &Ray, rec: &HitRecord<'_>, scattered: &Ray) -> Rgb {
+        let (wo, wi) = Self::local(r, rec, scattered);
+        if wo.z <= 0.0 {
+            return Rgb::ZERO;
+        }
+
+        Self::evaluate(&self.parameters(rec), wo, wi).0
+    }
+
+    fn scattering_pdf(&self, r: &Ray, rec: &HitRecord<'_>, scattered: &Ray) -> f64 {
+        let (wo, wi) = Self::local(r, rec, scattered);
+        if wo.z <= 0.0 {
+            return 0.0;
+        }
+
+        Self::evaluate(&self.parameters(rec), wo, wi).1
+    }
+}
+
+/// Schlick's approximation of the Fresnel reflectance with the reflectance `f0` at normal
+/// incidence.
+fn schlick(f0: Rgb, cos: f64) -> Rgb {
+    // f0 + (1 - f0) * (1 - cos)^5
+    (Rgb::ONE - f0).mul_add((1.0 - cos.clamp(0.0, 1.0)).powi(5), f0)
+}
+
+/// The density of the generalized Trowbridge-Reitz distribution with γ = 1 that the clear coat
+/// uses for its longer tail, for microfacet normals at the cosine `cos` from the normal.
+fn gtr1(cos: f64, alpha: f64) -> f64 {
+    if cos <= 0.0 {
+        return 0.0;
+    }
+    let alpha2 = alpha * alpha;
+
+    (alpha2 - 1.0) / (PI * alpha2.ln() * (alpha2 - 1.0).mul_add(cos * cos, 1.0))
+}
+
+/// A microfacet normal sampled from [`gtr1`] times its cosine.
+fn sample_gtr1(alpha: f64, rng: &mut dyn RngCore) -> Vec3 {
+    let alpha2 = alpha * alpha;
+    let cos2 = (1.0 - alpha2.powf(1.0 - CLOSED_OPEN_01.sample(rng))) / (1.0 - alpha2);
+    let sin = (1.0 - cos2).max(0.0).sqrt();
+    let (sin_phi, cos_phi) = (2.0 * PI * CLOSED_OPEN_01.sample(rng)).sin_cos();
+
+    Vec3::newf(sin * cos_phi, sin * sin_phi, cos2.sqrt())
+}
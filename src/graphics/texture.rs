@@ -1,3 +1,7 @@
+use std::path::Path;
+
+use image::{DynamicImage, ImageResult};
+
 use crate::{
     graphics::Perlin,
     math::{Point, Rgb},
@@ -81,3 +85,130 @@ impl Texture for Noise {
         Rgb::ONE * self.perlin.noise(p)
     }
 }
+
+/// Texture from an image file, wrapped around the surface by its texture coordinates and filtered
+/// bilinearly.
+///
+/// Colors are decoded from sRGB by [`Image::open`]; normal maps, heights and other data are read
+/// as they are stored by [`Image::open_linear`].
+pub struct Image {
+    width: usize,
+    height: usize,
+    pixels: Vec<Rgb>,
+}
+
+impl Image {
+    /// Load a color texture from an image file. Images stored as integers are decoded from sRGB,
+    /// while floating-point images, such as OpenEXR, are already linear.
+    ///
+    /// # Errors
+    ///
+    /// If the image can't be opened or decoded.
+    pub fn open(path: impl AsRef<Path>) -> ImageResult<Self> {
+        let image = image::open(path)?;
+        let linear = matches!(
+            image,
+            DynamicImage::ImageRgb32F(_) | DynamicImage::ImageRgba32F(_)
+        );
+
+        let mut texture = Self::from_image(image);
+        if !linear {
+            for pixel in &mut texture.pixels {
+                *pixel = Rgb::newf(
+                    srgb_to_linear(pixel.x),
+                    srgb_to_linear(pixel.y),
+                    srgb_to_linear(pixel.z),
+                );
+            }
+        }
+        Ok(texture)
+    }
+
+    /// Load a texture from an image file with its values as they are stored, for normal maps,
+    /// heights and other data that are not colors.
+    ///
+    /// # Errors
+    ///
+    /// If the image can't be opened or decoded.
+    pub fn open_linear(path: impl AsRef<Path>) -> ImageResult<Self> {
+        Ok(Self::from_image(image::open(path)?))
+    }
+
+    fn from_image(image: DynamicImage) -> Self {
+        let image = image.into_rgb32f();
+
+        Self {
+            width: image.width() as usize,
+            height: image.height() as usize,
+            pixels: image
+                .pixels()
+                .map(|&image::Rgb([r, g, b])| Rgb::newf(f64::from(r), f64::from(g), f64::from(b)))
+                .collect(),
+        }
+    }
+
+    /// Load the alpha channel of an image file as a scalar texture, such as a mask for
+    /// [`Masked`](crate::object::Masked). Images without an alpha channel are opaque.
+    ///
+    /// # Errors
+    ///
+    /// If the image can't be opened or decoded.
+    pub fn open_alpha(path: impl AsRef<Path>) -> ImageResult<Self> {
+        let image = image::open(path)?.into_rgba32f();
+
+        Ok(Self {
+            width: image.width() as usize,
+            height: image.height() as usize,
+            pixels: image
+                .pixels()
+                .map(|&image::Rgba([_, _, _, a])| Rgb::new_all(f64::from(a)))
+                .collect(),
+        })
+    }
+
+    /// Decode colors stored with `gamma`, like 2.2, in an image loaded by [`Image::open_linear`].
+    #[must_use]
+    pub fn with_gamma(mut self, gamma: f64) -> Self {
+        for pixel in &mut self.pixels {
+            *pixel = Rgb::newf(
+                pixel.x.max(0.0).powf(gamma),
+                pixel.y.max(0.0).powf(gamma),
+                pixel.z.max(0.0).powf(gamma),
+            );
+        }
+        self
+    }
+
+    fn pixel(&self, x: isize, y: isize) -> Rgb {
+        let x = x.rem_euclid(self.width as isize) as usize;
+        let y = y.rem_euclid(self.height as isize) as usize;
+
+        self.pixels[y * self.width + x]
+    }
+}
+
+impl Texture for Image {
+    fn value(&self, u: f64, v: f64, _p: &Point) -> Rgb {
+        // The first row is the top of the image, where `v` is 1.
+        let x = u.mul_add(self.width as f64, -0.5);
+        let y = (1.0 - v).mul_add(self.height as f64, -0.5);
+        let (x0, y0) = (x.floor(), y.floor());
+        let (tx, ty) = (x - x0, y - y0);
+        let (x0, y0) = (x0 as isize, y0 as isize);
+
+        let top = (self.pixel(x0 + 1, y0) - self.pixel(x0, y0)).mul_add(tx, self.pixel(x0, y0));
+        let bottom = (self.pixel(x0 + 1, y0 + 1) - self.pixel(x0, y0 + 1))
+            .mul_add(tx, self.pixel(x0, y0 + 1));
+
+        (bottom - top).mul_add(ty, top)
+    }
+}
+
+/// The linear value of an sRGB-encoded component.
+fn srgb_to_linear(c: f64) -> f64 {
+    if c <= 0.04045 {
+        c / 12.92
+    } else {
+        ((c + 0.055) / 1.055).powf(2.4)
+    }
+}
@@ -0,0 +1,157 @@
+use std::{
+    f64::consts::PI,
+    ops::{Add, Div, Mul, Sub},
+    sync::Arc,
+};
+
+use crate::{
+    graphics::{HitRecord, Solid, Texture},
+    math::Rgb,
+    spectrum,
+};
+
+/// A thin transparent film over a surface, like oil on water or the oxide layer of anodized metal,
+/// whose reflections from its two sides interfere to color the surface.
+#[derive(Clone)]
+#[non_exhaustive]
+pub struct ThinFilm {
+    /// The thickness of the film in nanometres, from the first channel of the texture.
+    pub thickness: Arc<dyn Texture>,
+    /// The refractive index of the film.
+    pub idx: f64,
+}
+
+impl ThinFilm {
+    pub const fn new(thickness: Arc<dyn Texture>, idx: f64) -> Self {
+        Self { thickness, idx }
+    }
+
+    /// A film of the same `thickness` everywhere, in nanometres.
+    pub fn uniform(thickness: f64, idx: f64) -> Self {
+        Self::new(Arc::new(Solid::new(Rgb::new_all(thickness))), idx)
+    }
+
+    /// The fraction of light arriving at the cosine `cos` from the normal that the film at `rec`
+    /// reflects, between a medium with the refractive index `above` and a substrate with the
+    /// complex index `below` + *i*`k` at each wavelength.
+    ///
+    /// The reflectance at `wavelength` in every channel when rendering spectra, and otherwise the
+    /// color reflected from white light.
+    pub(super) fn reflectance(
+        &self,
+        rec: &HitRecord<'_>,
+        wavelength: Option<f64>,
+        cos: f64,
+        above: f64,
+        below: impl Fn(f64) -> (f64, f64),
+    ) -> Rgb {
+        let thickness = self.thickness.value(rec.u, rec.v, &rec.point).x.max(0.0);
+        let at = |lambda: f64| {
+            let (eta, k) = below(lambda);
+            self.airy(cos, above, Complex::new(eta, k), thickness, lambda)
+        };
+
+        wavelength.map_or_else(
+            || spectrum::integrate(at),
+            |lambda| Rgb::new_all(at(lambda)),
+        )
+    }
+
+    /// The reflectance at `lambda` nanometres, summing the waves reflected back and forth inside
+    /// the film, averaged over both polarizations.
+    fn airy(&self, cos: f64, above: f64, below: Complex, thickness: f64, lambda: f64) -> f64 {
+        let cos = cos.clamp(0.0, 1.0);
+        let sin2 = above * above * cos.mul_add(-cos, 1.0);
+        // The refractive index times the cosine of the angle of the wave from the normal, in each
+        // medium; complex for absorbing media and evanescent waves.
+        let q = |n: Complex| (n * n - Complex::new(sin2, 0.0)).sqrt();
+        let (n0, n1, n2) = (Complex::new(above, 0.0), Complex::new(self.idx, 0.0), below);
+        let (q0, q1, q2) = (Complex::new(above * cos, 0.0), q(n1), q(n2));
+
+        let s = |qi: Complex, qj: Complex| (qi - qj) / (qi + qj);
+        let p = |ni: Complex, qi: Complex, nj: Complex, qj: Complex| {
+            (nj * nj * qi - ni * ni * qj) / (nj * nj * qi + ni * ni * qj)
+        };
+        // The phase gained by a wave crossing the film and back.
+        let phase = (Complex::new(0.0, 4.0 * PI * thickness / lambda) * q1).exp();
+        let airy = |r01: Complex, r12: Complex| {
+            let r = (r01 + r12 * phase) / (Complex::new(1.0, 0.0) + r01 * r12 * phase);
+            r.norm_squared().min(1.0)
+        };
+
+        0.5 * (airy(s(q0, q1), s(q1, q2)) + airy(p(n0, q0, n1, q1), p(n1, q1, n2, q2)))
+    }
+}
+
+/// A complex number.
+#[derive(Clone, Copy)]
+struct Complex {
+    re: f64,
+    im: f64,
+}
+
+impl Complex {
+    const fn new(re: f64, im: f64) -> Self {
+        Self { re, im }
+    }
+
+    fn norm_squared(self) -> f64 {
+        self.re.mul_add(self.re, self.im * self.im)
+    }
+
+    /// The principal square root, with a non-negative real part.
+    fn sqrt(self) -> Self {
+        let norm = self.norm_squared().sqrt();
+        let re = (0.5 * (norm + self.re)).max(0.0).sqrt();
+        let im = (0.5 * (norm - self.re)).max(0.0).sqrt();
+
+        Self::new(re, im.copysign(self.im))
+    }
+
+    fn exp(self) -> Self {
+        let (sin, cos) = self.im.sin_cos();
+        let magnitude = self.re.exp();
+
+        Self::new(magnitude * cos, magnitude * sin)
+    }
+}
+
+impl Add for Complex {
+    type Output = Self;
+
+    fn add(self, rhs: Self) -> Self {
+        Self::new(self.re + rhs.re, self.im + rhs.im)
+    }
+}
+
+impl Sub for Complex {
+    type Output = Self;
+
+    fn sub(self, rhs: Self) -> Self {
+        Self::new(self.re - rhs.re, self.im - rhs.im)
+    }
+}
+
+impl Mul for Complex {
+    type Output = Self;
+
+    fn mul(self, rhs: Self) -> Self {
+        Self::new(
+            self.re.mul_add(rhs.re, -(self.im * rhs.im)),
+            self.re.mul_add(rhs.im, self.im * rhs.re),
+        )
+    }
+}
+
+impl Div for Complex {
+    type Output = Self;
+
+    fn div(self, rhs: Self) -> Self {
+        let norm = rhs.norm_squared();
+
+        Self::new(
+            self.re.mul_add(rhs.re, self.im * rhs.im) / norm,
+            self.im.mul_add(rhs.re, -(self.re * rhs.im)) / norm,
+        )
+    }
+}
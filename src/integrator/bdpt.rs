@@ -0,0 +1,683 @@
+use std::f64::consts::PI;
+
+use indicatif::ProgressBar;
+use rand::prelude::*;
+
+use crate::{
+    camera::Camera,
+    film::{Film, Splats},
+    graphics::{CosinePdf, Face, Flight, Hit, HitRecord, Pdf, Ray, Scatter},
+    integrator::{self, Integrator},
+    math::{Point, Rgb, Vec3},
+    rng::CLOSED_OPEN_01,
+    scene::Scene,
+    spectrum::Wavelengths,
+};
+
+/// Bidirectional path tracer, which connects every vertex of a path traced from the camera to
+/// every vertex of a path traced from a light, and weights each connection with the balance
+/// heuristic.
+///
+/// Paths from lights connected directly to the camera land on arbitrary pixels, so they are only
+/// included by [`Integrator::render`]; [`Integrator::li`] weights the remaining strategies
+/// without them.
+#[non_exhaustive]
+pub struct Bdpt {
+    /// The maximum number of bounces along a path.
+    pub max_depth: usize,
+    /// The number of bounces before subpaths may be randomly terminated.
+    pub min_bounces: usize,
+    /// The maximum component of a sample's color, if any.
+    pub clamp: Option<f64>,
+}
+
+impl Bdpt {
+    pub const fn new(max_depth: usize, min_bounces: usize, clamp: Option<f64>) -> Self {
+        Self {
+            max_depth,
+            min_bounces,
+            clamp,
+        }
+    }
+
+    /// The color seen along the camera ray `r`, splatting the light subpath's direct connections
+    /// to the camera onto `sensor` if there is one.
+    fn sample(
+        &self,
+        r: &Ray,
+        scene: &Scene,
+        sensor: Option<&Sensor<'_>>,
+        rng: &mut dyn RngCore,
+    ) -> Rgb {
+        let mut camera_path = vec![Vertex::camera(r.origin, Rgb::ONE, r.t)];
+        let pdf_dir = sensor.map_or(0.0, |sensor| sensor.pdf_dir(r.direction));
+        let escaped = self.walk(
+            scene,
+            *r,
+            Rgb::ONE,
+            pdf_dir,
+            &mut camera_path,
+            self.max_depth + 2,
+            rng,
+        );
+        let light_path = self.light_path(scene, r.t, rng);
+
+        // Nothing but the camera subpath can reach the environment. Rays escaping from a surface
+        // are weighted against the chance that the environment was sampled from it instead.
+        let mut color = escaped.map_or(Rgb::ZERO, |(beta, ray, pdf_dir)| {
+            let weight = if camera_path.len() > 1 && pdf_dir > 0.0 {
+                integrator::environment_weight(scene, ray.direction, pdf_dir)
+            } else {
+                1.0
+            };
+            weight * beta * scene.environment.color(&ray)
+        });
+
+        for t in 1..=camera_path.len() {
+            for s in 0..=light_path.len() {
+                if s + t < 2 || s + t - 2 > self.max_depth {
+                    continue;
+                }
+
+                if t == 1 {
+                    let Some(sensor) = sensor else {
+                        continue;
+                    };
+                    if let Some((index, splat)) =
+                        Self::splat(scene, sensor, &light_path, &camera_path, s, rng)
+                    {
+                        sensor
+                            .splats
+                            .add(index, integrator::clamp(splat, self.clamp));
+                    }
+                } else {
+                    color += Self::connect(scene, sensor, &light_path, &camera_path, s, t);
+                }
+            }
+        }
+
+        // Analytic lights are only found by shadow rays, so they are connected to each camera
+        // vertex without weighting against the other strategies. The environment is sampled from
+        // each camera vertex like the path tracer does.
+        for pt in camera_path
+            .iter()
+            .skip(1)
+            .take(self.max_depth)
+            .filter(|pt| !pt.delta)
+        {
+            if let Kind::Surface {
+                ref rec,
+                ref incoming,
+            } = pt.kind
+            {
+                color += pt.beta
+                    * (integrator::analytic_light(incoming, scene, rec, Wavelengths::RGB, rng)
+                        + integrator::environment_light(
+                            incoming,
+                            scene,
+                            rec,
+                            Wavelengths::RGB,
+                            rng,
+                        ));
+            }
+        }
+
+        integrator::clamp(color, self.clamp)
+    }
+
+    /// A subpath starting from a point sampled on the scene's lights.
+    fn light_path<'a>(
+        &self,
+        scene: &'a Scene,
+        time: f64,
+        rng: &mut dyn RngCore,
+    ) -> Vec<Vertex<'a>> {
+        if scene.lights.is_empty() {
+            return Vec::new();
+        }
+        let Some((rec, pdf_pos)) = scene.lights.sample_surface(rng) else {
+            return Vec::new();
+        };
+
+        let emission = CosinePdf::new(rec.normal);
+        let direction = emission.generate(rng);
+        let pdf_dir = emission.value(direction);
+        let towards = Ray::new(rec.point + direction, -direction, time);
+        let le = rec.mat.emitted(&towards, &rec.facing(&towards));
+        if pdf_pos <= 0.0 || pdf_dir <= 0.0 || le.near_zero() {
+            return Vec::new();
+        }
+
+        let ray = Ray::new(rec.point, direction, time);
+        let beta = le * (rec.normal.dot(direction.unit()) / (pdf_pos * pdf_dir));
+        let mut path = vec![Vertex::light(rec, pdf_pos, time)];
+        self.walk(
+            scene,
+            ray,
+            beta,
+            pdf_dir,
+            &mut path,
+            self.max_depth + 1,
+            rng,
+        );
+
+        path
+    }
+
+    /// Extend `path` by tracing `ray` until it escapes the scene, is absorbed or terminated, or
+    /// `path` has `max_vertices` vertices. `beta` is the throughput of the path so far, and
+    /// `pdf_dir` the probability density with which the direction of `ray` was sampled.
+    ///
+    /// If the ray escapes the scene, returns the throughput of the path, the escaping ray, and the
+    /// probability density with which its direction was sampled.
+    #[allow(clippy::too_many_arguments)]
+    fn walk<'a>(
+        &self,
+        scene: &'a Scene,
+        mut ray: Ray,
+        mut beta: Rgb,
+        mut pdf_dir: f64,
+        path: &mut Vec<Vertex<'a>>,
+        max_vertices: usize,
+        rng: &mut dyn RngCore,
+    ) -> Option<(Rgb, Ray, f64)> {
+        let mut throughput = Rgb::ONE;
+        let mut bounce = 0;
+        // Whether the last vertex scattered inside an object rather than on its surface.
+        let mut in_medium = false;
+
+        while path.len() < max_vertices {
+            let Some(rec) = scene.world.hit(&ray, 0.001, f64::INFINITY) else {
+                return Some((beta, ray, pdf_dir));
+            };
+            // Interactions inside an object scatter specularly, from vertices of their own.
+            let flight = integrator::medium_flight(&ray, &rec, Wavelengths::RGB, throughput, rng);
+            let (rec, interaction) = match flight {
+                Some(Flight::Scattered {
+                    ray: scattered,
+                    weight,
+                }) => {
+                    let t = (scattered.origin - ray.origin).len() / ray.direction.len();
+                    let normal = -ray.direction.unit();
+                    let rec = HitRecord::new(
+                        scattered.origin,
+                        normal,
+                        rec.mat,
+                        t,
+                        rec.u,
+                        rec.v,
+                        Face::Back,
+                    );
+                    (rec, Some(Scatter::specular(scattered, weight)))
+                }
+                Some(Flight::Crossed { weight }) => {
+                    beta = beta * weight;
+                    throughput = throughput * weight;
+                    (rec, None)
+                }
+                None => (rec, None),
+            };
+            let scattered_in_medium = interaction.is_some();
+
+            // The densities of paths through a medium leave out its transmittance, so vertices
+            // inside one are given none, like specular vertices.
+            let mut vertex = Vertex::surface(rec, ray, beta);
+            vertex.pdf_fwd = if scattered_in_medium {
+                0.0
+            } else {
+                path[path.len() - 1].convert(pdf_dir, &vertex)
+            };
+            path.push(vertex);
+            if path.len() == max_vertices {
+                break;
+            }
+
+            let [.., prev, vertex] = path.as_slice() else {
+                unreachable!("random walks start from an endpoint");
+            };
+            let Kind::Surface { ref rec, .. } = vertex.kind else {
+                unreachable!("random walks only add surface vertices");
+            };
+            let Some(scattered) = interaction.or_else(|| rec.mat.scatter(&ray, rec, rng)) else {
+                break;
+            };
+
+            // The density of sampling the previous vertex from the scattered direction.
+            let pdf_rev = if scattered.specular {
+                pdf_dir = 0.0;
+                0.0
+            } else {
+                pdf_dir = scattered.pdf;
+                let direction = scattered.ray.direction;
+                let reversed = Ray::new(rec.point + direction, -direction, ray.t);
+                let rec = rec.facing(&reversed);
+                rec.mat
+                    .scattering_pdf(&reversed, &rec, &vertex.ray_to(prev))
+            };
+            let pdf_rev = if in_medium {
+                0.0
+            } else {
+                vertex.convert(pdf_rev, prev)
+            };
+            in_medium = scattered_in_medium;
+
+            let n = path.len();
+            path[n - 1].delta = scattered.specular;
+            path[n - 2].pdf_rev = pdf_rev;
+
+            beta = beta * scattered.attenuation;
+            throughput = throughput * scattered.attenuation;
+            ray = scattered.ray;
+
+            bounce += 1;
+            if bounce >= self.min_bounces {
+                let survival = throughput.max_component().min(1.0);
+                if survival <= 0.0 || CLOSED_OPEN_01.sample(rng) >= survival {
+                    break;
+                }
+                throughput /= survival;
+                beta /= survival;
+            }
+        }
+
+        None
+    }
+
+    /// The weighted contribution of the first `s` vertices of the light subpath joined to the
+    /// first `t >= 2` vertices of the camera subpath.
+    fn connect(
+        scene: &Scene,
+        sensor: Option<&Sensor<'_>>,
+        light_path: &[Vertex<'_>],
+        camera_path: &[Vertex<'_>],
+        s: usize,
+        t: usize,
+    ) -> Rgb {
+        let pt = &camera_path[t - 1];
+
+        let color = if s == 0 {
+            // The camera subpath found a light by itself.
+            match pt.kind {
+                Kind::Surface {
+                    ref rec,
+                    ref incoming,
+                } => pt.beta * rec.mat.emitted(incoming, rec),
+                _ => Rgb::ZERO,
+            }
+        } else {
+            let qs = &light_path[s - 1];
+            if !qs.connectable() || !pt.connectable() {
+                return Rgb::ZERO;
+            }
+
+            let color = qs.beta * qs.f_cos(pt, sensor) * pt.f_cos(qs, sensor) * pt.beta
+                / (qs.point - pt.point).len_squared();
+            if color.near_zero() || !visible(scene, pt, qs) {
+                return Rgb::ZERO;
+            }
+            color
+        };
+        if color.near_zero() {
+            return Rgb::ZERO;
+        }
+
+        color * mis_weight(scene, sensor, light_path, camera_path, None, s, t)
+    }
+
+    /// The weighted contribution of the first `s` vertices of the light subpath joined to a point
+    /// sampled on the lens, and the index of the pixel it lands on.
+    fn splat(
+        scene: &Scene,
+        sensor: &Sensor<'_>,
+        light_path: &[Vertex<'_>],
+        camera_path: &[Vertex<'_>],
+        s: usize,
+        rng: &mut dyn RngCore,
+    ) -> Option<(usize, Rgb)> {
+        let qs = &light_path[s - 1];
+        if !qs.connectable() {
+            return None;
+        }
+
+        let lens = sensor.camera.sample_lens(rng);
+        let (u, v) = sensor.camera.project(lens, qs.point)?;
+        let index = sensor.film.pixel_index(u, v)?;
+
+        let pt = Vertex::camera(lens, Rgb::new_all(sensor.camera.lens_area()), qs.time);
+        let color = qs.beta * qs.f_cos(&pt, Some(sensor)) * pt.f_cos(qs, Some(sensor)) * pt.beta
+            / (qs.point - pt.point).len_squared();
+        if color.near_zero() || !visible(scene, &pt, qs) {
+            return None;
+        }
+
+        let weight = mis_weight(
+            scene,
+            Some(sensor),
+            light_path,
+            camera_path,
+            Some(&pt),
+            s,
+            1,
+        );
+        Some((index, color * weight))
+    }
+}
+
+impl Integrator for Bdpt {
+    fn li(&self, r: &Ray, scene: &Scene, rng: &mut dyn RngCore) -> Rgb {
+        self.sample(r, scene, None, rng)
+    }
+
+    fn render(&self, scene: &Scene, camera: &Camera, film: &Film, bar: &ProgressBar) -> Vec<Rgb> {
+        let sensor = Sensor::new(camera, film);
+        let mut pixels = film.render(camera, bar, |r, rng| {
+            self.sample(r, scene, Some(&sensor), rng)
+        });
+
+        sensor.splats.add_to(&mut pixels);
+        pixels
+    }
+}
+
+/// The camera as seen by light subpaths, and the pixels they are splatted onto.
+struct Sensor<'a> {
+    camera: &'a Camera,
+    film: &'a Film,
+    /// The area of the sampled viewport at a distance of 1 from the lens.
+    area: f64,
+    splats: Splats,
+}
+
+impl<'a> Sensor<'a> {
+    fn new(camera: &'a Camera, film: &'a Film) -> Self {
+        Self {
+            camera,
+            film,
+            area: camera.viewport_area() * film.viewport_scale(),
+            splats: Splats::new(film),
+        }
+    }
+
+    /// The probability density, with respect to solid angle, of a camera ray along `direction`.
+    fn pdf_dir(&self, direction: Vec3) -> f64 {
+        let cos = self.camera.cos_theta(direction);
+        if cos <= 0.0 {
+            return 0.0;
+        }
+
+        (self.area * cos.powi(3)).recip()
+    }
+
+    /// The importance emitted by the camera along `direction`, normalized so that a camera ray
+    /// sampled from the lens and viewport carries a throughput of 1.
+    fn importance(&self, direction: Vec3) -> f64 {
+        let cos = self.camera.cos_theta(direction);
+        if cos <= 0.0 {
+            return 0.0;
+        }
+
+        (self.area * self.camera.lens_area() * cos.powi(4)).recip()
+    }
+}
+
+enum Kind<'a> {
+    Camera,
+    /// A point sampled on a light.
+    Light(HitRecord<'a>),
+    /// A point hit by `incoming`, with `rec` facing it.
+    Surface {
+        rec: HitRecord<'a>,
+        incoming: Ray,
+    },
+}
+
+/// A vertex of a camera or light subpath. Probability densities are with respect to area at the
+/// vertex.
+struct Vertex<'a> {
+    kind: Kind<'a>,
+    point: Point,
+    /// The throughput of the subpath up to the vertex.
+    beta: Rgb,
+    /// Whether the vertex scattered its outgoing ray specularly, so it cannot be connected to.
+    delta: bool,
+    /// Whether the vertex is on the boundary of or inside an object filled with a medium. Shadow
+    /// rays do not account for the light absorbed and scattered by the medium, so it cannot be
+    /// connected to either.
+    medium: bool,
+    /// The density of sampling the vertex from the previous vertex of its subpath.
+    pdf_fwd: f64,
+    /// The density of sampling the vertex from the next vertex, as if the path were traced in the
+    /// opposite direction.
+    pdf_rev: f64,
+    time: f64,
+}
+
+impl<'a> Vertex<'a> {
+    const fn camera(point: Point, beta: Rgb, time: f64) -> Self {
+        Self {
+            kind: Kind::Camera,
+            point,
+            beta,
+            delta: false,
+            medium: false,
+            pdf_fwd: 0.0,
+            pdf_rev: 0.0,
+            time,
+        }
+    }
+
+    fn light(rec: HitRecord<'a>, pdf_pos: f64, time: f64) -> Self {
+        Self {
+            point: rec.point,
+            medium: rec.mat.medium().is_some(),
+            kind: Kind::Light(rec),
+            beta: Rgb::new_all(pdf_pos.recip()),
+            delta: false,
+            pdf_fwd: pdf_pos,
+            pdf_rev: 0.0,
+            time,
+        }
+    }
+
+    fn surface(rec: HitRecord<'a>, incoming: Ray, beta: Rgb) -> Self {
+        Self {
+            point: rec.point,
+            medium: rec.mat.medium().is_some(),
+            kind: Kind::Surface { rec, incoming },
+            beta,
+            delta: false,
+            pdf_fwd: 0.0,
+            pdf_rev: 0.0,
+            time: incoming.t,
+        }
+    }
+
+    /// Whether a connection can be made from the vertex to a vertex of another subpath.
+    const fn connectable(&self) -> bool {
+        !self.delta && !self.medium
+    }
+
+    const fn rec(&self) -> Option<&HitRecord<'a>> {
+        match self.kind {
+            Kind::Camera => None,
+            Kind::Light(ref rec) | Kind::Surface { ref rec, .. } => Some(rec),
+        }
+    }
+
+    fn ray_to(&self, next: &Self) -> Ray {
+        Ray::new(self.point, next.point - self.point, self.time)
+    }
+
+    /// Convert a density with respect to solid angle at `self` to a density with respect to area
+    /// at `next`.
+    fn convert(&self, pdf_dir: f64, next: &Self) -> f64 {
+        let d = next.point - self.point;
+        let distance_squared = d.len_squared();
+        if distance_squared <= 0.0 {
+            return 0.0;
+        }
+
+        let pdf = pdf_dir / distance_squared;
+        next.rec().map_or(pdf, |rec| {
+            pdf * rec.normal.dot(d).abs() / distance_squared.sqrt()
+        })
+    }
+
+    /// The light scattered, emitted or, for the camera, importance emitted from `self` towards
+    /// `next`, multiplied by the cosine of the angle between that direction and the surface.
+    fn f_cos(&self, next: &Self, sensor: Option<&Sensor<'_>>) -> Rgb {
+        let direction = next.point - self.point;
+
+        match self.kind {
+            Kind::Camera => sensor.map_or(Rgb::ZERO, |sensor| {
+                Rgb::new_all(sensor.importance(direction) * sensor.camera.cos_theta(direction))
+            }),
+            Kind::Light(ref rec) => {
+                let towards = Ray::new(next.point, -direction, self.time);
+                let cos = rec.outward_normal().dot(direction.unit()).max(0.0);
+                cos * rec.mat.emitted(&towards, &rec.facing(&towards))
+            }
+            Kind::Surface {
+                ref rec,
+                ref incoming,
+            } => rec.mat.attenuation(incoming, rec, &self.ray_to(next)),
+        }
+    }
+
+    /// The density of sampling `next` from `self`, given that `self` was reached from `prev`.
+    fn pdf(&self, prev: Option<&Self>, next: &Self, sensor: Option<&Sensor<'_>>) -> f64 {
+        let pdf_dir = match self.kind {
+            Kind::Camera => sensor.map_or(0.0, |sensor| sensor.pdf_dir(next.point - self.point)),
+            Kind::Light(_) => return self.pdf_light(next),
+            Kind::Surface {
+                ref rec,
+                ref incoming,
+            } => {
+                let incoming = prev.map_or(*incoming, |prev| prev.ray_to(self));
+                let rec = rec.facing(&incoming);
+                rec.mat.scattering_pdf(&incoming, &rec, &self.ray_to(next))
+            }
+        };
+
+        self.convert(pdf_dir, next)
+    }
+
+    /// The density of a light subpath starting at `self` sampling `next` as its second vertex.
+    fn pdf_light(&self, next: &Self) -> f64 {
+        let Some(rec) = self.rec() else {
+            return 0.0;
+        };
+        let cos = rec
+            .outward_normal()
+            .dot((next.point - self.point).unit())
+            .max(0.0);
+
+        self.convert(cos / PI, next)
+    }
+
+    /// The density of a light subpath starting at `self`, seen from `prev`.
+    fn pdf_light_origin(&self, scene: &Scene, prev: &Self) -> f64 {
+        scene
+            .lights
+            .surface_pdf(prev.point, self.point - prev.point)
+    }
+}
+
+/// Whether nothing lies between `a` and `b`.
+fn visible(scene: &Scene, a: &Vertex<'_>, b: &Vertex<'_>) -> bool {
+    let d = b.point - a.point;
+    let distance = d.len();
+
+    scene
+        .world
+        .hit(
+            &Ray::new(a.point, d / distance, a.time),
+            0.001,
+            distance - 0.001,
+        )
+        .is_none()
+}
+
+/// The balance heuristic weight of the strategy joining `s` light vertices to `t` camera vertices,
+/// with `sampled` replacing the last camera vertex if it was sampled for the connection.
+///
+/// Only the ratios of the densities of each strategy to this one are needed, and they follow from
+/// the forward and reverse densities of the vertices where the strategies differ.
+fn mis_weight(
+    scene: &Scene,
+    sensor: Option<&Sensor<'_>>,
+    light_path: &[Vertex<'_>],
+    camera_path: &[Vertex<'_>],
+    sampled: Option<&Vertex<'_>>,
+    s: usize,
+    t: usize,
+) -> f64 {
+    let pt = sampled.unwrap_or(&camera_path[t - 1]);
+    let pt_minus = t.checked_sub(2).map(|i| &camera_path[i]);
+    let qs = s.checked_sub(1).map(|i| &light_path[i]);
+    let qs_minus = s.checked_sub(2).map(|i| &light_path[i]);
+
+    // Forward density, reverse density and whether each vertex cannot be connected to, with the
+    // densities at the connection recomputed for the joined path.
+    let mut camera: Vec<_> = camera_path[..t]
+        .iter()
+        .map(|v| (v.pdf_fwd, v.pdf_rev, !v.connectable()))
+        .collect();
+    let mut light: Vec<_> = light_path[..s]
+        .iter()
+        .map(|v| (v.pdf_fwd, v.pdf_rev, !v.connectable()))
+        .collect();
+
+    camera[t - 1].1 = match qs {
+        Some(qs) => qs.pdf(qs_minus, pt, sensor),
+        None => {
+            // No other strategy can sample a light missing from the scene's lights.
+            let Some(pt_minus) = pt_minus else {
+                return 1.0;
+            };
+            let pdf = pt.pdf_light_origin(scene, pt_minus);
+            if pdf <= 0.0 {
+                return 1.0;
+            }
+            pdf
+        }
+    };
+    camera[t - 1].2 = pt.medium;
+    if let Some(pt_minus) = pt_minus {
+        camera[t - 2].1 = qs.map_or_else(
+            || pt.pdf_light(pt_minus),
+            |qs| pt.pdf(Some(qs), pt_minus, sensor),
+        );
+    }
+    if let Some(qs) = qs {
+        light[s - 1].1 = pt.pdf(pt_minus, qs, sensor);
+        light[s - 1].2 = qs.medium;
+        if let Some(qs_minus) = qs_minus {
+            light[s - 2].1 = qs.pdf(Some(pt), qs_minus, sensor);
+        }
+    }
+
+    // Specular vertices have no density, which cancels out of the ratios.
+    let remap = |pdf: f64| if pdf == 0.0 { 1.0 } else { pdf };
+    let mut sum = 0.0;
+
+    // Connections to the camera are only made when there is a sensor to splat them onto.
+    let first = if sensor.is_some() { 1 } else { 2 };
+    let mut ratio = 1.0;
+    for i in (first..t).rev() {
+        ratio *= remap(camera[i].1) / remap(camera[i].0);
+        if !camera[i].2 && !camera[i - 1].2 {
+            sum += ratio;
+        }
+    }
+
+    let mut ratio = 1.0;
+    for i in (0..s).rev() {
+        ratio *= remap(light[i].1) / remap(light[i].0);
+        if !light[i].2 && (i == 0 || !light[i - 1].2) {
+            sum += ratio;
+        }
+    }
+
+    (1.0 + sum).recip()
+}
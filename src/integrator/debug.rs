@@ -0,0 +1,130 @@
+use std::ptr;
+
+use rand::prelude::*;
+
+use crate::{
+    graphics::{CosinePdf, Hit, HitRecord, Material, Pdf, Ray},
+    integrator::Integrator,
+    math::Rgb,
+    rng,
+    scene::Scene,
+};
+
+/// Shades the first surface hit by its outward normal, mapping each component from [-1, 1] to
+/// [0, 1].
+pub struct Normals;
+
+impl Integrator for Normals {
+    fn li(&self, r: &Ray, scene: &Scene, _rng: &mut dyn RngCore) -> Rgb {
+        first_hit(r, scene).map_or(Rgb::ZERO, |rec| 0.5 * (rec.outward_normal() + Rgb::ONE))
+    }
+}
+
+/// Shades the first surface hit by its texture coordinates, with `u` in red and `v` in green.
+pub struct Uv;
+
+impl Integrator for Uv {
+    fn li(&self, r: &Ray, scene: &Scene, _rng: &mut dyn RngCore) -> Rgb {
+        first_hit(r, scene).map_or(Rgb::ZERO, |rec| Rgb::newf(rec.u, rec.v, 0.0))
+    }
+}
+
+/// Shades the first surface hit by its distance from the camera, from white up close to black at
+/// `far`.
+pub struct Depth {
+    pub far: f64,
+}
+
+impl Integrator for Depth {
+    fn li(&self, r: &Ray, scene: &Scene, _rng: &mut dyn RngCore) -> Rgb {
+        first_hit(r, scene).map_or(Rgb::ZERO, |rec| {
+            let distance = rec.t * r.direction.len();
+            Rgb::new_all(1.0 - (distance / self.far).min(1.0))
+        })
+    }
+}
+
+/// Shades the first surface hit by the fraction of its hemisphere that is not occluded within
+/// `radius`.
+pub struct AmbientOcclusion {
+    pub radius: f64,
+}
+
+impl Integrator for AmbientOcclusion {
+    fn li(&self, r: &Ray, scene: &Scene, rng: &mut dyn RngCore) -> Rgb {
+        let Some(rec) = first_hit(r, scene) else {
+            return Rgb::ZERO;
+        };
+
+        // Cosine-weighted sampling cancels the cosine term of the occlusion integral.
+        let direction = CosinePdf::new(rec.normal).generate(rng);
+        let occluder = scene.world.hit(
+            &Ray::new(rec.point, direction, r.t),
+            0.001,
+            self.radius / direction.len(),
+        );
+
+        if occluder.is_some() {
+            Rgb::ZERO
+        } else {
+            Rgb::ONE
+        }
+    }
+}
+
+/// Shades each pixel by the number of [`Bvh`](crate::graphics::Bvh) nodes visited to find the
+/// first surface hit, from blue for none to red for `max_visits` or more.
+pub struct BvhCost {
+    pub max_visits: usize,
+}
+
+impl Integrator for BvhCost {
+    fn li(&self, r: &Ray, scene: &Scene, _rng: &mut dyn RngCore) -> Rgb {
+        let (_, visits) = scene.world.hit_with_visits(r, 0.001, f64::INFINITY);
+
+        // Logarithmic scale, since the cost grows with the log of the number of objects.
+        let t = ((visits as f64).ln_1p() / (self.max_visits as f64).ln_1p()).min(1.0);
+        heat(t)
+    }
+}
+
+/// Shades the first surface hit with a color unique to its material or object.
+pub enum FalseColor {
+    Material,
+    Object,
+}
+
+impl Integrator for FalseColor {
+    fn li(&self, r: &Ray, scene: &Scene, _rng: &mut dyn RngCore) -> Rgb {
+        first_hit(r, scene).map_or(Rgb::ZERO, |rec| {
+            let id = match self {
+                Self::Material => ptr::from_ref::<dyn Material>(rec.mat).cast::<()>().addr(),
+                Self::Object => rec.object,
+            };
+            id_color(id)
+        })
+    }
+}
+
+fn first_hit<'a>(r: &Ray, scene: &'a Scene) -> Option<HitRecord<'a>> {
+    scene.world.hit(r, 0.001, f64::INFINITY)
+}
+
+/// A blue to green to red color ramp for `t` in [0, 1].
+fn heat(t: f64) -> Rgb {
+    if t < 0.5 {
+        let t = 2.0 * t;
+        Rgb::newf(0.0, t, 1.0 - t)
+    } else {
+        let t = 2.0f64.mul_add(t, -1.0);
+        Rgb::newf(t, 1.0 - t, 0.0)
+    }
+}
+
+/// A bright color derived from a hash of `id`.
+fn id_color(id: usize) -> Rgb {
+    let z = rng::splitmix64(id as u64);
+
+    let channel = |shift: u64| 0.8f64.mul_add(f64::from((z >> shift) as u8) / 255.0, 0.2);
+    Rgb::newf(channel(0), channel(8), channel(16))
+}
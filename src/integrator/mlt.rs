@@ -0,0 +1,289 @@
+use std::f64::consts::PI;
+
+use indicatif::ProgressBar;
+use rand::{prelude::*, rngs::SmallRng};
+use rayon::prelude::*;
+
+use crate::{
+    camera::Camera,
+    film::{Film, Splats},
+    graphics::Ray,
+    integrator::{Integrator, PathTracer},
+    math::Rgb,
+    rng::CLOSED_OPEN_01,
+    scene::Scene,
+};
+
+/// Primary sample space Metropolis light transport, layered on the path tracer.
+///
+/// Each path traced by the [`PathTracer`] is a function of the random numbers it draws, so paths
+/// are explored by mutating those numbers: small steps find paths near bright ones, and large
+/// steps draw new numbers to find bright paths elsewhere. Mutations are accepted in proportion to
+/// the luminance they carry, so Markov chains of paths spend their time where the light is.
+///
+/// Each sample per pixel is one mutation per pixel, spread over all the chains.
+#[non_exhaustive]
+pub struct Mlt {
+    pub path: PathTracer,
+    /// The number of independent paths traced to estimate the brightness of the image and to pick
+    /// the paths the chains start from.
+    pub bootstrap: usize,
+    /// The number of Markov chains.
+    pub chains: usize,
+    /// The standard deviation of a small step of each primary sample.
+    pub sigma: f64,
+    /// The probability of a mutation being a large step.
+    pub large_step_probability: f64,
+}
+
+impl Mlt {
+    pub const fn new(
+        path: PathTracer,
+        bootstrap: usize,
+        chains: usize,
+        sigma: f64,
+        large_step_probability: f64,
+    ) -> Self {
+        Self {
+            path,
+            bootstrap,
+            chains,
+            sigma,
+            large_step_probability,
+        }
+    }
+
+    /// The pixel and color of the path sampled from the current primary samples of `sampler`.
+    fn evaluate(
+        &self,
+        scene: &Scene,
+        camera: &Camera,
+        film: &Film,
+        sampler: &mut PrimarySamples,
+    ) -> (usize, Rgb) {
+        let (index, r) = film.sample_ray(camera, sampler);
+        let color = self.path.li(&r, scene, sampler);
+
+        // A chain that accepted a NaN or infinite color would never leave it.
+        if color.luminance().is_finite() {
+            (index, color)
+        } else {
+            (index, Rgb::ZERO)
+        }
+    }
+
+    fn sampler(&self, seed: u64) -> PrimarySamples {
+        PrimarySamples::new(seed, self.sigma, self.large_step_probability)
+    }
+}
+
+impl Integrator for Mlt {
+    fn li(&self, r: &Ray, scene: &Scene, rng: &mut dyn RngCore) -> Rgb {
+        self.path.li(r, scene, rng)
+    }
+
+    fn render(&self, scene: &Scene, camera: &Camera, film: &Film, bar: &ProgressBar) -> Vec<Rgb> {
+        let mut pixels = vec![Rgb::ZERO; film.pixel_count()];
+
+        // Paths are remade from the seed of their sampler when a chain starts from them.
+        let seeds: Vec<u64> = {
+            let mut rng = rand::rng();
+            (0..self.bootstrap).map(|_| rng.random()).collect()
+        };
+        let weights: Vec<f64> = seeds
+            .par_iter()
+            .map(|&seed| {
+                let mut sampler = self.sampler(seed);
+                self.evaluate(scene, camera, film, &mut sampler)
+                    .1
+                    .luminance()
+            })
+            .collect();
+        let cdf: Vec<f64> = weights
+            .iter()
+            .scan(0.0, |sum, weight| {
+                *sum += weight;
+                Some(*sum)
+            })
+            .collect();
+        let total = cdf.last().copied().unwrap_or(0.0);
+        if total <= 0.0 || self.chains == 0 {
+            return pixels;
+        }
+        let brightness = total / self.bootstrap as f64;
+
+        let mutations = film.samples_per_pixel as usize * film.pixel_count();
+        let splats = Splats::new(film);
+        bar.set_length(self.chains as u64);
+
+        (0..self.chains).into_par_iter().for_each(|chain| {
+            let mut rng = rand::rng();
+            let count = mutations / self.chains + usize::from(chain < mutations % self.chains);
+
+            let target = CLOSED_OPEN_01.sample(&mut rng) * total;
+            let start = cdf.partition_point(|&sum| sum <= target);
+            let mut sampler = self.sampler(seeds[start]);
+            let (mut index, mut color) = self.evaluate(scene, camera, film, &mut sampler);
+            // Chains starting from the same path would otherwise make the same mutations.
+            sampler.reseed(rng.random());
+
+            for _ in 0..count {
+                sampler.start_iteration();
+                let (proposed_index, proposed) = self.evaluate(scene, camera, film, &mut sampler);
+
+                let luminance = color.luminance();
+                let proposed_luminance = proposed.luminance();
+                // Chains start from and only move to paths that carry light.
+                let accept = (proposed_luminance / luminance).min(1.0);
+
+                // Both paths contribute in proportion to their chance of being the next state,
+                // which lowers the variance of rarely accepted paths.
+                if accept > 0.0 {
+                    splats.add(proposed_index, proposed * (accept / proposed_luminance));
+                }
+                if accept < 1.0 {
+                    splats.add(index, color * ((1.0 - accept) / luminance));
+                }
+
+                if CLOSED_OPEN_01.sample(&mut rng) < accept {
+                    (index, color) = (proposed_index, proposed);
+                    sampler.accept();
+                } else {
+                    sampler.reject();
+                }
+            }
+
+            bar.inc(1);
+        });
+
+        // Each splat has a luminance of 1, so the image is scaled to the luminance estimated by
+        // the bootstrap paths. Films hold the sum of each pixel's samples, and there are as many
+        // mutations per pixel as samples.
+        splats.add_to(&mut pixels);
+        pixels.into_iter().map(|pixel| brightness * pixel).collect()
+    }
+}
+
+/// Random numbers in [0, 1) drawn from a vector of primary samples, which is mutated between
+/// iterations so that the path sampled from them changes a little or, in a large step, entirely.
+struct PrimarySamples {
+    rng: SmallRng,
+    samples: Vec<PrimarySample>,
+    /// The index of the next sample to draw in the current iteration.
+    index: usize,
+    iteration: usize,
+    large_step: bool,
+    /// The last iteration whose large step was accepted.
+    last_large_step: usize,
+    sigma: f64,
+    large_step_probability: f64,
+}
+
+struct PrimarySample {
+    value: f64,
+    /// The iteration in which the value was last mutated.
+    modified: usize,
+    /// The value and modification iteration before the current iteration, restored if its
+    /// mutation is rejected.
+    backup: (f64, usize),
+}
+
+impl PrimarySamples {
+    /// Samples whose first iteration is a large step, drawn from a generator seeded with `seed`.
+    fn new(seed: u64, sigma: f64, large_step_probability: f64) -> Self {
+        Self {
+            rng: SmallRng::seed_from_u64(seed),
+            samples: Vec::new(),
+            index: 0,
+            iteration: 0,
+            large_step: true,
+            last_large_step: 0,
+            sigma,
+            large_step_probability,
+        }
+    }
+
+    fn reseed(&mut self, seed: u64) {
+        self.rng = SmallRng::seed_from_u64(seed);
+    }
+
+    /// Start drawing the samples of a new mutation.
+    fn start_iteration(&mut self) {
+        self.iteration += 1;
+        self.large_step = CLOSED_OPEN_01.sample(&mut self.rng) < self.large_step_probability;
+        self.index = 0;
+    }
+
+    const fn accept(&mut self) {
+        if self.large_step {
+            self.last_large_step = self.iteration;
+        }
+    }
+
+    /// Restore the samples mutated in the current iteration.
+    fn reject(&mut self) {
+        for sample in &mut self.samples {
+            if sample.modified == self.iteration {
+                (sample.value, sample.modified) = sample.backup;
+            }
+        }
+        self.iteration -= 1;
+    }
+
+    /// The next sample of the current iteration, mutated lazily since paths draw different numbers
+    /// of samples.
+    fn next(&mut self) -> f64 {
+        // Samples drawn for the first time take any value, as the ones mutated from them would.
+        if self.index == self.samples.len() {
+            let value = CLOSED_OPEN_01.sample(&mut self.rng);
+            self.samples.push(PrimarySample {
+                value,
+                modified: self.iteration,
+                backup: (value, self.iteration),
+            });
+        }
+        let sample = &mut self.samples[self.index];
+        self.index += 1;
+
+        // Samples left unused since the last accepted large step are replaced by it.
+        if sample.modified < self.last_large_step {
+            sample.value = CLOSED_OPEN_01.sample(&mut self.rng);
+            sample.modified = self.last_large_step;
+        }
+
+        sample.backup = (sample.value, sample.modified);
+        if self.large_step {
+            sample.value = CLOSED_OPEN_01.sample(&mut self.rng);
+        } else {
+            // The small steps missed since the sample was last used add up to one normally
+            // distributed step with their combined variance.
+            let steps = (self.iteration - sample.modified) as f64;
+            let u1 = CLOSED_OPEN_01.sample(&mut self.rng);
+            let u2 = CLOSED_OPEN_01.sample(&mut self.rng);
+            let normal = (-2.0 * (1.0 - u1).ln()).sqrt() * (2.0 * PI * u2).cos();
+
+            sample.value = (self.sigma * steps.sqrt()).mul_add(normal, sample.value);
+            sample.value -= sample.value.floor();
+        }
+        sample.modified = self.iteration;
+
+        sample.value
+    }
+}
+
+impl RngCore for PrimarySamples {
+    fn next_u32(&mut self) -> u32 {
+        (self.next() * 2f64.powi(32)) as u32
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        (self.next() * 2f64.powi(64)) as u64
+    }
+
+    fn fill_bytes(&mut self, dst: &mut [u8]) {
+        for chunk in dst.chunks_mut(8) {
+            let bytes = self.next_u64().to_le_bytes();
+            chunk.copy_from_slice(&bytes[..chunk.len()]);
+        }
+    }
+}
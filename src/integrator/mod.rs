@@ -0,0 +1,252 @@
+//! Light transport integrators.
+
+mod bdpt;
+mod debug;
+mod mlt;
+mod path;
+mod sppm;
+
+pub use bdpt::Bdpt;
+pub use debug::{AmbientOcclusion, BvhCost, Depth, FalseColor, Normals, Uv};
+use indicatif::ProgressBar;
+pub use mlt::Mlt;
+pub use path::PathTracer;
+use rand::prelude::*;
+pub use sppm::Sppm;
+
+use crate::{
+    camera::Camera,
+    film::{Film, Rgba},
+    graphics::{Face, Flight, Hit, HitPdf, HitRecord, Matte, Pdf, Ray, power_heuristic},
+    math::{Rgb, Vec3},
+    scene::Scene,
+    spectrum::Wavelengths,
+};
+
+/// Trait for algorithms that estimate the light arriving along camera [`Ray`]s.
+pub trait Integrator: Send + Sync {
+    /// The color seen along a camera ray.
+    fn li(&self, r: &Ray, scene: &Scene, rng: &mut dyn RngCore) -> Rgb;
+
+    /// The sum of the samples of each pixel of `film`, as returned by [`Film::render`].
+    /// Integrators that also add light to pixels other than the one being sampled override this.
+    fn render(&self, scene: &Scene, camera: &Camera, film: &Film, bar: &ProgressBar) -> Vec<Rgb> {
+        film.render(camera, bar, |r, rng| self.li(r, scene, rng))
+    }
+
+    /// The sums of the samples of each pixel of `film` and their opacities, compositing the scene
+    /// over a background that shows through the environment and materials with a [`Matte`].
+    fn render_matte(
+        &self,
+        scene: &Scene,
+        camera: &Camera,
+        film: &Film,
+        bar: &ProgressBar,
+    ) -> Vec<Rgba> {
+        film.render(camera, bar, |r, rng| matte(self, r, scene, rng))
+    }
+}
+
+/// `color` scaled down so that its largest component is at most `max`, if any.
+fn clamp(color: Rgb, max: Option<f64>) -> Rgb {
+    match max {
+        Some(max) if color.max_component() > max => color * (max / color.max_component()),
+        _ => color,
+    }
+}
+
+/// Light arriving at a hit point from the scene's analytic lights, through a shadow ray to each.
+/// Nothing else finds these lights, so their light needs no weighting.
+fn analytic_light(
+    r: &Ray,
+    scene: &Scene,
+    rec: &HitRecord<'_>,
+    wavelengths: Wavelengths,
+    rng: &mut dyn RngCore,
+) -> Vec3 {
+    let mut color = Vec3::ZERO;
+
+    for light in &scene.analytic_lights {
+        let Some(incident) = light.sample(rec.point, rng) else {
+            continue;
+        };
+        let shadow = Ray::new(rec.point, incident.direction, r.t);
+        let attenuation = rec.mat.attenuation(r, rec, &shadow);
+
+        if !attenuation.near_zero()
+            && scene
+                .world
+                .hit(&shadow, 0.001, incident.distance - 0.001)
+                .is_none()
+        {
+            color += wavelengths.uplift(attenuation) * wavelengths.uplift(incident.light);
+        }
+    }
+
+    color
+}
+
+/// Light arriving at a hit point directly from a direction sampled from the scene's lights,
+/// weighted against the chance that the hit point's material samples the same direction.
+fn emissive_light(
+    r: &Ray,
+    scene: &Scene,
+    rec: &HitRecord<'_>,
+    wavelengths: Wavelengths,
+    rng: &mut dyn RngCore,
+) -> Vec3 {
+    let lights = HitPdf::new(&scene.lights, rec.point);
+    let shadow = Ray::new(rec.point, lights.generate(rng), r.t);
+    let pdf = lights.value(shadow.direction);
+    if pdf <= 0.0 {
+        return Vec3::ZERO;
+    }
+
+    let attenuation = rec.mat.attenuation(r, rec, &shadow);
+    if attenuation.near_zero() {
+        return Vec3::ZERO;
+    }
+    let weight = power_heuristic(pdf, rec.mat.scattering_pdf(r, rec, &shadow));
+
+    // The first object hit by the shadow ray is either the light or an occluder, which can itself
+    // be emissive.
+    scene
+        .world
+        .hit(&shadow, 0.001, f64::INFINITY)
+        .map_or(Vec3::ZERO, |light| {
+            let emitted = wavelengths.uplift(light.mat.emitted(&shadow, &light));
+            weight * wavelengths.uplift(attenuation) * emitted / pdf
+        })
+}
+
+/// Light arriving at a hit point from a direction sampled from the scene's environment, weighted
+/// against the chance that the hit point's material samples the same direction.
+fn environment_light(
+    r: &Ray,
+    scene: &Scene,
+    rec: &HitRecord<'_>,
+    wavelengths: Wavelengths,
+    rng: &mut dyn RngCore,
+) -> Vec3 {
+    let Some((direction, pdf)) = scene.environment.sample(rng) else {
+        return Vec3::ZERO;
+    };
+    let shadow = Ray::new(rec.point, direction, r.t);
+    let attenuation = rec.mat.attenuation(r, rec, &shadow);
+    if pdf <= 0.0
+        || attenuation.near_zero()
+        || scene.world.hit(&shadow, 0.001, f64::INFINITY).is_some()
+    {
+        return Vec3::ZERO;
+    }
+
+    let weight = power_heuristic(pdf, rec.mat.scattering_pdf(r, rec, &shadow));
+    let light = wavelengths.uplift(scene.environment.color(&shadow));
+    weight * wavelengths.uplift(attenuation) * light / pdf
+}
+
+/// The weight of light found by a ray escaping the scene along a direction sampled from a
+/// material with density `pdf`, against the chance that [`environment_light`] sampled it.
+fn environment_weight(scene: &Scene, direction: Vec3, pdf: f64) -> f64 {
+    power_heuristic(pdf, scene.environment.pdf(direction))
+}
+
+/// Where light arriving at `rec` along `r` first interacted with the medium filling the object
+/// that was hit, if it travelled through one from inside, for a path carrying `throughput`.
+fn medium_flight(
+    r: &Ray,
+    rec: &HitRecord<'_>,
+    wavelengths: Wavelengths,
+    throughput: Vec3,
+    rng: &mut dyn RngCore,
+) -> Option<Flight> {
+    let medium = rec.mat.medium().filter(|_| rec.face == Face::Back)?;
+
+    Some(medium.sample(r, rec.t * r.direction.len(), wavelengths, throughput, rng))
+}
+
+/// The color seen along a camera ray by `integrator` and its opacity, compositing the scene over a
+/// background.
+fn matte<I: Integrator + ?Sized>(
+    integrator: &I,
+    r: &Ray,
+    scene: &Scene,
+    rng: &mut dyn RngCore,
+) -> Rgba {
+    let Some(rec) = scene.world.hit(r, 0.001, f64::INFINITY) else {
+        return Rgba::TRANSPARENT;
+    };
+
+    match rec.mat.matte() {
+        None => Rgba::opaque(integrator.li(r, scene, rng)),
+        Some(Matte::Holdout) => Rgba::TRANSPARENT,
+        Some(Matte::ShadowCatcher) => Rgba::new(
+            caught_reflection(integrator, r, scene, &rec, rng),
+            caught_shadow(r, scene, &rec, rng),
+        ),
+    }
+}
+
+/// The light that a shadow catcher at `rec` reflects along `r` from the rest of the scene, rather
+/// than from the background.
+fn caught_reflection<I: Integrator + ?Sized>(
+    integrator: &I,
+    r: &Ray,
+    scene: &Scene,
+    rec: &HitRecord<'_>,
+    rng: &mut dyn RngCore,
+) -> Rgb {
+    let Some(scattered) = rec.mat.scatter(r, rec, rng) else {
+        return Rgb::ZERO;
+    };
+
+    match scene.world.hit(&scattered.ray, 0.001, f64::INFINITY) {
+        Some(hit) if hit.mat.matte().is_none() => {
+            scattered.attenuation * integrator.li(&scattered.ray, scene, rng)
+        }
+        _ => Rgb::ZERO,
+    }
+}
+
+/// The fraction of the light from the environment and the analytic lights, which light the
+/// background, that the rest of the scene blocks from reaching a shadow catcher at `rec`.
+fn caught_shadow(r: &Ray, scene: &Scene, rec: &HitRecord<'_>, rng: &mut dyn RngCore) -> f64 {
+    // Light arriving with the rest of the scene, and with the background alone.
+    let mut arriving = 0.0;
+    let mut unshadowed = 0.0;
+    let mut add = |direction: Vec3, distance: f64, light: Rgb, pdf: f64| {
+        let shadow = Ray::new(rec.point, direction, r.t);
+        let light = (rec.mat.attenuation(r, rec, &shadow) * light).luminance() / pdf;
+        let occluder = scene.world.hit(&shadow, 0.001, distance - 0.001);
+
+        // The background casts its own shadows, unless the scene blocks the light before it.
+        if occluder
+            .as_ref()
+            .is_some_and(|hit| hit.mat.matte().is_some())
+        {
+            return;
+        }
+        unshadowed += light;
+        if occluder.is_none() {
+            arriving += light;
+        }
+    };
+
+    if let Some((direction, pdf)) = scene.environment.sample(rng).filter(|&(_, pdf)| pdf > 0.0) {
+        let light = scene
+            .environment
+            .color(&Ray::new(rec.point, direction, r.t));
+        add(direction, f64::INFINITY, light, pdf);
+    }
+    for light in &scene.analytic_lights {
+        if let Some(incident) = light.sample(rec.point, rng) {
+            add(incident.direction, incident.distance, incident.light, 1.0);
+        }
+    }
+
+    if unshadowed > 0.0 {
+        1.0 - arriving / unshadowed
+    } else {
+        0.0
+    }
+}
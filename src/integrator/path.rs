@@ -0,0 +1,143 @@
+use rand::prelude::*;
+
+use crate::{
+    graphics::{Flight, Hit, Ray, power_heuristic},
+    integrator::{self, Integrator},
+    math::{Rgb, Vec3},
+    rng::CLOSED_OPEN_01,
+    scene::Scene,
+    spectrum::Wavelengths,
+};
+
+/// Unidirectional path tracer with direct light sampling and Russian roulette.
+#[non_exhaustive]
+pub struct PathTracer {
+    /// The maximum number of bounces along a path.
+    pub max_depth: usize,
+    /// The number of bounces before paths may be randomly terminated.
+    pub min_bounces: usize,
+    /// The maximum component of a sample's color, if any. Clamping removes fireflies from rare
+    /// bright paths at the cost of losing energy.
+    pub clamp: Option<f64>,
+    /// Whether each path carries a few wavelengths of light instead of RGB colors.
+    pub spectral: bool,
+}
+
+impl PathTracer {
+    pub const fn new(max_depth: usize, min_bounces: usize, clamp: Option<f64>) -> Self {
+        Self {
+            max_depth,
+            min_bounces,
+            clamp,
+            spectral: false,
+        }
+    }
+
+    /// Trace wavelengths of light instead of RGB colors, so that dispersive materials split light
+    /// into colors.
+    #[must_use]
+    pub const fn with_spectral(mut self, spectral: bool) -> Self {
+        self.spectral = spectral;
+        self
+    }
+
+    /// Whether the path survives Russian roulette after `bounce`, boosting its `throughput` if so.
+    /// Paths that carry little light are terminated with a probability that keeps the estimate
+    /// unbiased by boosting the paths that survive.
+    fn survive(&self, bounce: usize, throughput: &mut Vec3, rng: &mut dyn RngCore) -> bool {
+        if bounce + 1 < self.min_bounces {
+            return true;
+        }
+        let survival = throughput.max_component().min(1.0);
+        if survival <= 0.0 || CLOSED_OPEN_01.sample(rng) >= survival {
+            return false;
+        }
+        *throughput /= survival;
+        true
+    }
+}
+
+impl Integrator for PathTracer {
+    fn li(&self, r: &Ray, scene: &Scene, rng: &mut dyn RngCore) -> Rgb {
+        let mut wavelengths = if self.spectral {
+            Wavelengths::sample(rng)
+        } else {
+            Wavelengths::RGB
+        };
+        // Light is converted to a color as it is found, since later bounces may stop carrying some
+        // wavelengths.
+        let mut color = Rgb::ZERO;
+        let mut throughput = Vec3::ONE;
+        let mut ray = r.with_wavelength(wavelengths.hero());
+        // Emission found by a ray sampled from a material is weighted against the chance that the
+        // previous bounce sampled the same light directly, and likewise for the environment.
+        let mut emitted_weight = 1.0;
+        let mut environment_weight = 1.0;
+
+        // If the maximum diffuse reflection depth is reached, no more light is gathered.
+        for bounce in 0..self.max_depth {
+            let Some(ref rec) = scene.world.hit(&ray, 0.001, f64::INFINITY) else {
+                let light = wavelengths.uplift(scene.environment.color(&ray));
+                color += wavelengths.to_rgb(environment_weight * throughput * light);
+                break;
+            };
+            if rec.mat.is_dispersive() {
+                wavelengths.terminate_secondary();
+            }
+
+            match integrator::medium_flight(&ray, rec, wavelengths, throughput, rng) {
+                Some(Flight::Scattered {
+                    ray: scattered,
+                    weight,
+                }) => {
+                    // Nothing can be sampled directly from inside the object.
+                    throughput = throughput * weight;
+                    ray = scattered.with_wavelength(wavelengths.hero());
+                    emitted_weight = 1.0;
+                    environment_weight = 1.0;
+                    if !self.survive(bounce, &mut throughput, rng) {
+                        break;
+                    }
+                    continue;
+                }
+                Some(Flight::Crossed { weight }) => throughput = throughput * weight,
+                None => {}
+            }
+
+            let emitted = wavelengths.uplift(rec.mat.emitted(&ray, rec));
+            color += wavelengths.to_rgb(emitted_weight * throughput * emitted);
+            let Some(scattered) = rec.mat.scatter(&ray, rec, rng) else {
+                break;
+            };
+
+            if scattered.specular {
+                emitted_weight = 1.0;
+                environment_weight = 1.0;
+            } else {
+                let light = integrator::analytic_light(&ray, scene, rec, wavelengths, rng)
+                    + integrator::environment_light(&ray, scene, rec, wavelengths, rng);
+                color += wavelengths.to_rgb(throughput * light);
+                environment_weight =
+                    integrator::environment_weight(scene, scattered.ray.direction, scattered.pdf);
+
+                if scene.lights.is_empty() {
+                    emitted_weight = 1.0;
+                } else {
+                    let light = integrator::emissive_light(&ray, scene, rec, wavelengths, rng);
+                    color += wavelengths.to_rgb(throughput * light);
+                    let light_pdf = scene.lights.pdf_value(rec.point, scattered.ray.direction);
+                    emitted_weight = power_heuristic(scattered.pdf, light_pdf);
+                }
+            }
+
+            throughput = throughput * wavelengths.uplift(scattered.attenuation);
+            ray = scattered.ray.with_wavelength(wavelengths.hero());
+
+            if !self.survive(bounce, &mut throughput, rng) {
+                break;
+            }
+        }
+
+        integrator::clamp(color, self.clamp)
+    }
+}
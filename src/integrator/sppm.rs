@@ -0,0 +1,441 @@
+use std::f64::consts::PI;
+
+use indicatif::ProgressBar;
+use rand::prelude::*;
+use rayon::prelude::*;
+
+use crate::{
+    camera::Camera,
+    film::Film,
+    graphics::{CosinePdf, Flight, Hit, HitRecord, KdTree, Pdf, Ray, power_heuristic},
+    integrator::{self, Integrator},
+    math::{Onb, Point, Rgb, Vec3},
+    rng::CLOSED_OPEN_01,
+    scene::Scene,
+    spectrum::Wavelengths,
+};
+
+/// The fraction of the photons gathered in each iteration that are kept when the gathering radius
+/// shrinks.
+const ALPHA: f64 = 2.0 / 3.0;
+
+/// Stochastic progressive photon mapper, for rendering caustics.
+///
+/// Photons traced from the lights and environment are gathered around the first diffuse surface
+/// seen by each pixel, within a radius that shrinks over the iterations. Direct lighting is
+/// sampled from both the lights and the surfaces' materials, like the path tracer does.
+///
+/// Each sample per pixel is one iteration of a camera pass and a photon pass, so photons are only
+/// gathered by [`Integrator::render`]; [`Integrator::li`] only estimates direct lighting.
+#[non_exhaustive]
+pub struct Sppm {
+    /// The maximum number of bounces along camera and photon paths.
+    pub max_depth: usize,
+    /// The number of bounces before photon paths may be randomly terminated.
+    pub min_bounces: usize,
+    /// The number of photons traced per iteration, or one per pixel if `None`.
+    pub photons: Option<usize>,
+    /// The initial gathering radius, in pixel widths at the distance of the surface seen.
+    pub radius: f64,
+}
+
+impl Sppm {
+    pub const fn new(
+        max_depth: usize,
+        min_bounces: usize,
+        photons: Option<usize>,
+        radius: f64,
+    ) -> Self {
+        Self {
+            max_depth,
+            min_bounces,
+            photons,
+            radius,
+        }
+    }
+
+    /// Follow `r` through specular and glossy bounces to the first diffuse surface, which gathers
+    /// photons, or to a glossy surface at the last bounce. Returns the light found along the way
+    /// and directly lighting that surface, and the surface if one was found.
+    ///
+    /// Glossy reflections are followed rather than gathered, since blurring their photons over the
+    /// gathering radius would blur what they reflect.
+    fn visible_point<'a>(
+        &self,
+        r: &Ray,
+        scene: &'a Scene,
+        rng: &mut dyn RngCore,
+    ) -> (Rgb, Option<VisiblePoint<'a>>) {
+        let mut color = Rgb::ZERO;
+        let mut beta = Rgb::ONE;
+        let mut distance = 0.0;
+        let mut ray = *r;
+        // Emission found by a ray sampled from a glossy surface is weighted against the chance
+        // that the surface sampled the same light directly, and likewise for the environment.
+        let mut emitted_weight = 1.0;
+        let mut environment_weight = 1.0;
+
+        for bounce in 0..self.max_depth {
+            let Some(rec) = scene.world.hit(&ray, 0.001, f64::INFINITY) else {
+                color += environment_weight * beta * scene.environment.color(&ray);
+                break;
+            };
+            match integrator::medium_flight(&ray, &rec, Wavelengths::RGB, beta, rng) {
+                Some(Flight::Scattered {
+                    ray: scattered,
+                    weight,
+                }) => {
+                    distance += (scattered.origin - ray.origin).len();
+                    beta = beta * weight;
+                    ray = scattered;
+                    emitted_weight = 1.0;
+                    environment_weight = 1.0;
+                    continue;
+                }
+                Some(Flight::Crossed { weight }) => beta = beta * weight,
+                None => {}
+            }
+
+            distance = rec.t.mul_add(ray.direction.len(), distance);
+            color += emitted_weight * beta * rec.mat.emitted(&ray, &rec);
+            let Some(scattered) = rec.mat.scatter(&ray, &rec, rng) else {
+                break;
+            };
+
+            if scattered.specular {
+                emitted_weight = 1.0;
+                environment_weight = 1.0;
+            } else {
+                color += beta * Self::direct(&ray, scene, &rec, rng);
+
+                if rec.mat.is_diffuse() || bounce + 1 == self.max_depth {
+                    color += beta * Self::scattered_light(&ray, scene, &rec, rng);
+                    return (
+                        color,
+                        Some(VisiblePoint {
+                            rec,
+                            ray,
+                            beta,
+                            distance,
+                        }),
+                    );
+                }
+
+                environment_weight =
+                    integrator::environment_weight(scene, scattered.ray.direction, scattered.pdf);
+                emitted_weight = if scene.lights.is_empty() {
+                    1.0
+                } else {
+                    let light_pdf = scene.lights.pdf_value(rec.point, scattered.ray.direction);
+                    power_heuristic(scattered.pdf, light_pdf)
+                };
+            }
+
+            beta = beta * scattered.attenuation;
+            ray = scattered.ray;
+        }
+
+        (color, None)
+    }
+
+    /// Light arriving at a hit point directly along directions sampled from the scene's lights,
+    /// analytic lights and environment, weighted against the chance that the hit point's material
+    /// samples the same directions.
+    fn direct(r: &Ray, scene: &Scene, rec: &HitRecord<'_>, rng: &mut dyn RngCore) -> Rgb {
+        let mut color = integrator::analytic_light(r, scene, rec, Wavelengths::RGB, rng)
+            + integrator::environment_light(r, scene, rec, Wavelengths::RGB, rng);
+        if !scene.lights.is_empty() {
+            color += integrator::emissive_light(r, scene, rec, Wavelengths::RGB, rng);
+        }
+
+        color
+    }
+
+    /// Light arriving at a visible point directly along a direction sampled from its material,
+    /// weighted against the chance that [`Sppm::direct`] sampled the same direction. Photons are
+    /// only gathered after their first bounce, so they add the rest of the light.
+    fn scattered_light(r: &Ray, scene: &Scene, rec: &HitRecord<'_>, rng: &mut dyn RngCore) -> Rgb {
+        let Some(scattered) = rec.mat.scatter(r, rec, rng) else {
+            return Rgb::ZERO;
+        };
+        let ray = scattered.ray;
+
+        let Some(light) = scene.world.hit(&ray, 0.001, f64::INFINITY) else {
+            let weight = if scattered.specular {
+                1.0
+            } else {
+                integrator::environment_weight(scene, ray.direction, scattered.pdf)
+            };
+            return weight * scattered.attenuation * scene.environment.color(&ray);
+        };
+
+        let weight = if scattered.specular || scene.lights.is_empty() {
+            1.0
+        } else {
+            power_heuristic(
+                scattered.pdf,
+                scene.lights.pdf_value(rec.point, ray.direction),
+            )
+        };
+        weight * scattered.attenuation * light.mat.emitted(&ray, &light)
+    }
+
+    /// A photon leaving the scene's lights, analytic lights or environment, and its power. Each of
+    /// the analytic lights, the environment and the other lights as a whole are equally likely to
+    /// emit it.
+    fn emit(scene: &Scene, time: f64, rng: &mut dyn RngCore) -> Option<(Ray, Rgb)> {
+        let lights = usize::from(!scene.lights.is_empty());
+        let environment = usize::from(!scene.environment.is_black());
+        let sources = lights + scene.analytic_lights.len() + environment;
+        if sources == 0 {
+            return None;
+        }
+        let source = rng.random_range(0..sources);
+        let probability = (sources as f64).recip();
+
+        if source < lights {
+            let (rec, pdf_pos) = scene.lights.sample_surface(rng)?;
+            let emission = CosinePdf::new(rec.normal);
+            let direction = emission.generate(rng);
+            let pdf_dir = emission.value(direction);
+            if pdf_pos <= 0.0 || pdf_dir <= 0.0 {
+                return None;
+            }
+
+            let towards = Ray::new(rec.point + direction, -direction, time);
+            let le = rec.mat.emitted(&towards, &rec.facing(&towards));
+            let cos = rec.normal.dot(direction.unit());
+
+            Some((
+                Ray::new(rec.point, direction, time),
+                le * (cos / (pdf_pos * pdf_dir * probability)),
+            ))
+        } else if let Some(light) = scene.analytic_lights.get(source - lights) {
+            let (center, radius) = scene.bounding_sphere(time)?;
+            let (ray, power) = light.emit(center, radius, time, rng)?;
+
+            Some((ray, power / probability))
+        } else {
+            // Photons from the environment cross a disc facing their direction that covers the
+            // scene's bounding sphere.
+            let (center, radius) = scene.bounding_sphere(time)?;
+            let (towards, pdf_dir) = scene.environment.sample(rng)?;
+            if pdf_dir <= 0.0 {
+                return None;
+            }
+
+            let direction = -towards;
+            let offset = Onb::from_w(direction).local(radius * Vec3::random_in_unit_disc(rng));
+            let origin = direction.mul_add(-radius, center) + offset;
+
+            let le = scene.environment.color(&Ray::new(center, towards, time));
+            let pdf = pdf_dir * (PI * radius * radius).recip();
+
+            Some((Ray::new(origin, direction, time), le / (pdf * probability)))
+        }
+    }
+
+    /// The photons left on non-specular surfaces by a photon traced from the lights, after its
+    /// first bounce.
+    fn trace_photon(
+        &self,
+        scene: &Scene,
+        time: f64,
+        rng: &mut dyn RngCore,
+    ) -> Vec<(Point, Photon)> {
+        let mut photons = Vec::new();
+        let Some((mut ray, mut power)) = Self::emit(scene, time, rng) else {
+            return photons;
+        };
+        let mut throughput = Rgb::ONE;
+
+        for bounce in 0..self.max_depth {
+            let Some(rec) = scene.world.hit(&ray, 0.001, f64::INFINITY) else {
+                break;
+            };
+            let flight = integrator::medium_flight(&ray, &rec, Wavelengths::RGB, throughput, rng);
+            let (next, attenuation) = match flight {
+                Some(Flight::Scattered { ray, weight }) => (ray, weight),
+                flight => {
+                    if let Some(Flight::Crossed { weight }) = flight {
+                        power = power * weight;
+                        throughput = throughput * weight;
+                    }
+                    let Some(scattered) = rec.mat.scatter(&ray, &rec, rng) else {
+                        break;
+                    };
+
+                    if !scattered.specular && bounce > 0 {
+                        photons.push((
+                            rec.point,
+                            Photon {
+                                direction: ray.direction.unit(),
+                                power,
+                            },
+                        ));
+                    }
+                    (scattered.ray, scattered.attenuation)
+                }
+            };
+
+            power = power * attenuation;
+            throughput = throughput * attenuation;
+            ray = next;
+
+            if bounce + 1 >= self.min_bounces {
+                let survival = throughput.max_component().min(1.0);
+                if survival <= 0.0 || CLOSED_OPEN_01.sample(rng) >= survival {
+                    break;
+                }
+                throughput /= survival;
+                power /= survival;
+            }
+        }
+
+        photons
+    }
+}
+
+impl Integrator for Sppm {
+    fn li(&self, r: &Ray, scene: &Scene, rng: &mut dyn RngCore) -> Rgb {
+        self.visible_point(r, scene, rng).0
+    }
+
+    fn render(&self, scene: &Scene, camera: &Camera, film: &Film, bar: &ProgressBar) -> Vec<Rgb> {
+        let iterations = film.samples_per_pixel;
+        let photons = self.photons.unwrap_or_else(|| film.pixel_count());
+        // The width of a pixel at a distance of 1 from the camera.
+        let footprint =
+            (camera.viewport_area() * film.viewport_scale() / film.pixel_count() as f64).sqrt();
+
+        let mut pixels: Vec<PixelStats> =
+            (0..film.pixel_count()).map(|_| PixelStats::new()).collect();
+        bar.set_length(u64::from(iterations));
+
+        for _ in 0..iterations {
+            let visible: Vec<_> = pixels
+                .par_iter_mut()
+                .enumerate()
+                .map(|(i, pixel)| {
+                    let mut rng = rand::rng();
+                    let r = film.ray(camera, i, &mut rng);
+                    let (color, visible) = self.visible_point(&r, scene, &mut rng);
+
+                    pixel.direct += color;
+                    if let Some(ref visible) = visible {
+                        if pixel.radius <= 0.0 {
+                            pixel.radius = self.radius * footprint * visible.distance;
+                        }
+                    }
+                    visible
+                })
+                .collect();
+
+            let tree = KdTree::new(
+                (0..photons)
+                    .into_par_iter()
+                    .flat_map_iter(|_| {
+                        let mut rng = rand::rng();
+                        self.trace_photon(scene, camera.time(&mut rng), &mut rng)
+                    })
+                    .collect::<Vec<_>>(),
+            );
+
+            pixels
+                .par_iter_mut()
+                .zip(visible)
+                .for_each(|(pixel, visible)| {
+                    if let Some(visible) = visible {
+                        pixel.gather(&tree, &visible);
+                    }
+                });
+
+            bar.inc(1);
+        }
+
+        let samples = f64::from(iterations);
+        let emitted = samples * photons as f64;
+        pixels
+            .iter()
+            .map(|pixel| {
+                let indirect = if pixel.radius > 0.0 && emitted > 0.0 {
+                    pixel.flux / (emitted * PI * pixel.radius * pixel.radius)
+                } else {
+                    Rgb::ZERO
+                };
+                // Films hold the sum of each pixel's samples.
+                pixel.direct + samples * indirect
+            })
+            .collect()
+    }
+}
+
+/// A photon arriving at a surface.
+struct Photon {
+    /// The unit direction the photon was travelling in.
+    direction: Vec3,
+    power: Rgb,
+}
+
+/// The surface that gathers photons for a pixel in an iteration.
+struct VisiblePoint<'a> {
+    rec: HitRecord<'a>,
+    /// The ray that hit the surface.
+    ray: Ray,
+    /// The throughput of the path from the camera to the surface.
+    beta: Rgb,
+    /// The length of the path from the camera to the surface.
+    distance: f64,
+}
+
+/// The photon statistics of a pixel, accumulated over the iterations.
+struct PixelStats {
+    /// The sum of the direct lighting found in each iteration.
+    direct: Rgb,
+    /// The gathering radius, or 0 before the pixel has seen a surface.
+    radius: f64,
+    /// The number of photons gathered, reduced as the radius shrinks.
+    photons: f64,
+    /// The flux of the photons gathered within the radius.
+    flux: Rgb,
+}
+
+impl PixelStats {
+    const fn new() -> Self {
+        Self {
+            direct: Rgb::ZERO,
+            radius: 0.0,
+            photons: 0.0,
+            flux: Rgb::ZERO,
+        }
+    }
+
+    /// Gather the photons around `visible` and shrink the radius.
+    fn gather(&mut self, tree: &KdTree<Photon>, visible: &VisiblePoint<'_>) {
+        let rec = &visible.rec;
+        let mut flux = Rgb::ZERO;
+        let mut count = 0.0;
+
+        tree.within(rec.point, self.radius, |_, photon| {
+            count += 1.0;
+
+            // Materials give the BSDF times the cosine of the incoming direction, which the
+            // density estimate doesn't need.
+            let incoming = Ray::new(rec.point, -photon.direction, visible.ray.t);
+            let cos = photon.direction.dot(rec.normal).abs();
+            if cos > 1e-8 {
+                flux += photon.power * rec.mat.attenuation(&visible.ray, rec, &incoming) / cos;
+            }
+        });
+
+        if count > 0.0 {
+            let photons = ALPHA.mul_add(count, self.photons);
+            let radius = self.radius * (photons / (self.photons + count)).sqrt();
+
+            self.flux =
+                (self.flux + visible.beta * flux) * (radius * radius) / (self.radius * self.radius);
+            self.photons = photons;
+            self.radius = radius;
+        }
+    }
+}
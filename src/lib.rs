@@ -1,8 +1,17 @@
 #![warn(clippy::all, clippy::cargo, clippy::nursery, rust_2018_idioms)]
+// `image` and `rand` pull in different major versions of `bitflags`, `rand_core` and `zerocopy`
+// through their own dependencies, which can't be aligned from this crate.
+#![allow(clippy::multiple_crate_versions)]
 #![doc = include_str!("../README.md")]
 
 pub mod camera;
+pub mod environment;
+pub mod film;
 pub mod graphics;
+pub mod integrator;
+pub mod light;
 pub mod math;
 pub mod object;
 pub mod rng;
+pub mod scene;
+pub mod spectrum;
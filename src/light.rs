@@ -0,0 +1,247 @@
+//! Analytic lights.
+
+use std::f64::consts::PI;
+
+use rand::prelude::*;
+
+use crate::{
+    graphics::Ray,
+    math::{Onb, Point, Rgb, Vec3},
+};
+
+/// Creates a `Vec` of boxed lights that implement [`Light`].
+#[macro_export]
+macro_rules! lightlist {
+    ( $($x:expr),* $(,)? ) => {{
+        use sidewinder::light::Light;
+
+        let list: Vec<Box<dyn Light>> = vec![$(Box::new($x)),*];
+        list
+    }};
+}
+
+/// Trait for lights that are not objects in the scene. Rays never hit them, so they are only
+/// found by shadow rays from the points they light.
+pub trait Light: Send + Sync {
+    /// Light arriving at `point` along a direction sampled towards the light, divided by the
+    /// probability density of sampling it.
+    fn sample(&self, point: Point, rng: &mut dyn RngCore) -> Option<Incident>;
+
+    /// A ray of light leaving the light at `time`, and its power divided by the probability
+    /// density of sampling it. Lights outside the scene shine on the sphere bounding it, with
+    /// `center` and `radius`.
+    fn emit(
+        &self,
+        center: Point,
+        radius: f64,
+        time: f64,
+        rng: &mut dyn RngCore,
+    ) -> Option<(Ray, Rgb)>;
+}
+
+/// Light arriving at a point from a [`Light`].
+#[non_exhaustive]
+pub struct Incident {
+    /// The unit direction from the point towards the light.
+    pub direction: Vec3,
+    /// The distance from the point to the light, which is infinite for distant lights.
+    pub distance: f64,
+    pub light: Rgb,
+}
+
+/// A light shining equally in every direction from a point, with inverse-square falloff.
+pub struct PointLight {
+    position: Point,
+    /// The radiant intensity of the light.
+    intensity: Rgb,
+}
+
+impl PointLight {
+    pub const fn new(position: Point, intensity: Rgb) -> Self {
+        Self {
+            position,
+            intensity,
+        }
+    }
+}
+
+impl Light for PointLight {
+    fn sample(&self, point: Point, _rng: &mut dyn RngCore) -> Option<Incident> {
+        let offset = self.position - point;
+        let distance_squared = offset.len_squared();
+        if distance_squared <= 0.0 {
+            return None;
+        }
+
+        Some(Incident {
+            direction: offset.unit(),
+            distance: distance_squared.sqrt(),
+            light: self.intensity / distance_squared,
+        })
+    }
+
+    fn emit(
+        &self,
+        _center: Point,
+        _radius: f64,
+        time: f64,
+        rng: &mut dyn RngCore,
+    ) -> Option<(Ray, Rgb)> {
+        Some((
+            Ray::new(self.position, Vec3::random_unit_vec(rng), time),
+            4.0 * PI * self.intensity,
+        ))
+    }
+}
+
+/// A point light that only shines within a cone, fading out between an inner and an outer angle
+/// from the cone's axis.
+pub struct SpotLight {
+    position: Point,
+    /// The axis of the cone.
+    uvw: Onb,
+    /// The radiant intensity of the light along the axis.
+    intensity: Rgb,
+    cos_inner: f64,
+    cos_outer: f64,
+}
+
+impl SpotLight {
+    /// A spot light at `position` pointing at `target`, with full intensity within `inner` degrees
+    /// of the axis, and none beyond `outer` degrees.
+    pub fn new(position: Point, target: Point, intensity: Rgb, inner: f64, outer: f64) -> Self {
+        let outer = outer.clamp(0.0, 180.0);
+        let inner = inner.clamp(0.0, outer);
+
+        Self {
+            position,
+            uvw: Onb::from_w(target - position),
+            intensity,
+            cos_inner: inner.to_radians().cos(),
+            cos_outer: outer.to_radians().cos(),
+        }
+    }
+
+    /// The fraction of the intensity shining along a direction with the cosine `cos` from the
+    /// axis.
+    fn falloff(&self, cos: f64) -> f64 {
+        if cos >= self.cos_inner {
+            1.0
+        } else if cos <= self.cos_outer {
+            0.0
+        } else {
+            // Smoothstep from the outer cone to the inner cone.
+            let t = (cos - self.cos_outer) / (self.cos_inner - self.cos_outer);
+            t * t * 2.0f64.mul_add(-t, 3.0)
+        }
+    }
+}
+
+impl Light for SpotLight {
+    fn sample(&self, point: Point, _rng: &mut dyn RngCore) -> Option<Incident> {
+        let offset = self.position - point;
+        let distance_squared = offset.len_squared();
+        if distance_squared <= 0.0 {
+            return None;
+        }
+        let direction = offset.unit();
+        let falloff = self.falloff(-direction.dot(self.uvw.w));
+        if falloff <= 0.0 {
+            return None;
+        }
+
+        Some(Incident {
+            direction,
+            distance: distance_squared.sqrt(),
+            light: falloff * self.intensity / distance_squared,
+        })
+    }
+
+    fn emit(
+        &self,
+        _center: Point,
+        _radius: f64,
+        time: f64,
+        rng: &mut dyn RngCore,
+    ) -> Option<(Ray, Rgb)> {
+        let local = Vec3::random_in_cone(self.cos_outer, rng);
+        let pdf = (2.0 * PI * (1.0 - self.cos_outer)).recip();
+        let falloff = self.falloff(local.z);
+        if falloff <= 0.0 {
+            return None;
+        }
+
+        Some((
+            Ray::new(self.position, self.uvw.local(local), time),
+            falloff * self.intensity / pdf,
+        ))
+    }
+}
+
+/// A light infinitely far away, shining along a single direction, or from a disc of directions
+/// as seen from the ground, like the sun.
+pub struct DirectionalLight {
+    /// The direction light travels in.
+    uvw: Onb,
+    /// The irradiance of a surface facing the light.
+    irradiance: Rgb,
+    /// The cosine of the angular radius of the light's disc.
+    cos_radius: f64,
+}
+
+impl DirectionalLight {
+    pub fn new(direction: Vec3, irradiance: Rgb) -> Self {
+        Self {
+            uvw: Onb::from_w(direction),
+            irradiance,
+            cos_radius: 1.0,
+        }
+    }
+
+    /// Spread the light over a disc `diameter` degrees across, which softens its shadows. The sun
+    /// is about 0.53 degrees across.
+    #[must_use]
+    pub fn with_angular_diameter(mut self, diameter: f64) -> Self {
+        self.cos_radius = (0.5 * diameter.clamp(0.0, 180.0)).to_radians().cos();
+        self
+    }
+
+    /// A direction the light travels in, sampled uniformly from its disc.
+    fn direction(&self, rng: &mut dyn RngCore) -> Vec3 {
+        if self.cos_radius < 1.0 {
+            self.uvw.local(Vec3::random_in_cone(self.cos_radius, rng))
+        } else {
+            self.uvw.w
+        }
+    }
+}
+
+impl Light for DirectionalLight {
+    fn sample(&self, _point: Point, rng: &mut dyn RngCore) -> Option<Incident> {
+        // Each direction in the disc is as bright as the others, so their radiance divided by the
+        // density of sampling them is the irradiance of the whole disc.
+        Some(Incident {
+            direction: -self.direction(rng),
+            distance: f64::INFINITY,
+            light: self.irradiance,
+        })
+    }
+
+    fn emit(
+        &self,
+        center: Point,
+        radius: f64,
+        time: f64,
+        rng: &mut dyn RngCore,
+    ) -> Option<(Ray, Rgb)> {
+        // Light crosses a disc facing its direction that covers the scene's bounding sphere.
+        let direction = self.direction(rng);
+        let offset = Onb::from_w(direction).local(radius * Vec3::random_in_unit_disc(rng));
+        let origin = direction.mul_add(-radius, center) + offset;
+
+        Some((
+            Ray::new(origin, direction, time),
+            PI * radius * radius * self.irradiance,
+        ))
+    }
+}
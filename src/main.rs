@@ -1,28 +1,48 @@
 #![warn(clippy::all, clippy::cargo, clippy::nursery, rust_2018_idioms)]
+// `image` and `rand` pull in different major versions of `bitflags`, `rand_core` and `zerocopy`
+// through their own dependencies, which can't be aligned from this crate.
+#![allow(clippy::multiple_crate_versions)]
 #![doc = include_str!("../README.md")]
 
 use std::{
-    fs::{self, File, OpenOptions},
+    fs::{self, OpenOptions},
     io::{self, BufWriter, Write},
-    path::Path,
+    ops::Range,
+    path::{Path, PathBuf},
     time::Instant,
 };
 
-use anyhow::{Result, bail};
+use anyhow::{Context, Result, bail};
 use clap::Parser;
-use image::ImageBuffer;
+use image::{DynamicImage, ImageBuffer, Pixel};
 use indicatif::{HumanDuration, ProgressBar};
-use rand::distr::Distribution;
-use rayon::prelude::*;
 use sidewinder::{
-    camera::Camera,
-    math::{Point, Rgb, Vec3},
-    rng::CLOSED_OPEN_01,
+    camera::{Camera, Projection},
+    environment::EnvironmentMap,
+    film::{Film, Rgba},
+    integrator::{
+        AmbientOcclusion, Bdpt, BvhCost, Depth, FalseColor, Integrator, Mlt, Normals, PathTracer,
+        Sppm, Uv,
+    },
+    scene::Scene,
 };
 
 mod scene_1;
+mod scene_10;
+mod scene_11;
+mod scene_12;
+mod scene_13;
+mod scene_14;
+mod scene_15;
+mod scene_16;
 mod scene_2;
 mod scene_3;
+mod scene_4;
+mod scene_5;
+mod scene_6;
+mod scene_7;
+mod scene_8;
+mod scene_9;
 
 #[derive(clap::Parser)]
 #[clap(author, version, about, long_about = None)]
@@ -39,10 +59,104 @@ struct Args {
     #[clap(short, long = "samples", default_value_t = 50)]
     samples_per_pixel: u32,
 
-    /// Diffuse reflection recursion depth.
+    /// Maximum number of bounces along a path.
     #[clap(short = 'd', long = "depth", default_value_t = 100)]
     max_depth: usize,
 
+    /// Number of bounces before paths may be randomly terminated.
+    #[clap(long, default_value_t = 3)]
+    min_bounces: usize,
+
+    /// Maximum color component of each sample, to suppress fireflies.
+    #[clap(long, value_name = "MAX")]
+    clamp: Option<f64>,
+
+    /// Trace wavelengths of light instead of RGB colors, for dispersion. Only the `path` and `mlt`
+    /// integrators render spectra.
+    #[clap(long)]
+    spectral: bool,
+
+    /// Render the environment seen directly, holdouts and shadow catchers as transparent, for
+    /// compositing over a background. PNG and EXR images get an alpha channel. The `bdpt`, `sppm`
+    /// and `mlt` integrators don't render transparency.
+    #[clap(long)]
+    transparent: bool,
+
+    /// Integrator used to estimate the color of each sample.
+    #[clap(short, long, value_enum, default_value_t = IntegratorName::Path)]
+    integrator: IntegratorName,
+
+    /// Photons traced per iteration of the `sppm` integrator [default: one per pixel].
+    #[clap(long)]
+    photons: Option<usize>,
+
+    /// Initial photon gathering radius of the `sppm` integrator, in pixels.
+    #[clap(long, value_name = "PIXELS", default_value_t = 2.0)]
+    photon_radius: f64,
+
+    /// Independent paths traced by the `mlt` integrator to estimate the image's brightness.
+    #[clap(long, default_value_t = 100_000)]
+    bootstrap: usize,
+
+    /// Markov chains run by the `mlt` integrator.
+    #[clap(long, default_value_t = 1000)]
+    chains: usize,
+
+    /// Standard deviation of the small mutations of the `mlt` integrator.
+    #[clap(long, value_name = "SIGMA", default_value_t = 0.01)]
+    mutation_sigma: f64,
+
+    /// Probability of a large mutation in the `mlt` integrator.
+    #[clap(long, default_value_t = 0.3)]
+    large_step: f64,
+
+    /// Distance within which surfaces occlude ambient light, for the `ao` integrator.
+    #[clap(long, default_value_t = 1.0)]
+    ao_radius: f64,
+
+    /// Distance shaded black by the `depth` integrator.
+    #[clap(long, default_value_t = 100.0)]
+    far: f64,
+
+    /// Scene to render.
+    #[clap(long, value_enum, default_value_t = SceneName::TwoPerlinSpheres)]
+    scene: SceneName,
+
+    /// Projection of the camera, replacing the scene's. The `bdpt` integrator only renders the
+    /// perspective projection.
+    #[clap(long, value_enum)]
+    projection: Option<ProjectionName>,
+
+    /// Vertical field of view of the camera, replacing the scene's, which fisheye projections
+    /// cover across the image's height.
+    #[clap(long, value_name = "DEGREES")]
+    fov: Option<f64>,
+
+    /// Equirectangular HDR image of the light around the scene, replacing the scene's environment.
+    #[clap(long, value_name = "PATH")]
+    environment: Option<PathBuf>,
+
+    /// Rotation of the environment map around the vertical axis, in degrees.
+    #[clap(long, value_name = "DEGREES", default_value_t = 0.0)]
+    environment_rotation: f64,
+
+    /// Scale of the light from the environment map.
+    #[clap(long, default_value_t = 1.0)]
+    environment_intensity: f64,
+
+    /// Render a sequence of frames, e.g. `0..120` or `0..=119`. The output path must contain a
+    /// frame number pattern such as `%04d`.
+    #[clap(long, value_parser = parse_frames)]
+    frames: Option<Range<u32>>,
+
+    /// Frames per second of a frame sequence.
+    #[clap(long, default_value_t = 24.0)]
+    fps: f64,
+
+    /// Fraction of each frame's duration that the shutter is open.
+    #[clap(long, default_value_t = 0.5)]
+    shutter: f64,
+
     /// Output path.
     #[clap(value_name = "PATH")]
     output_path: Option<String>,
@@ -52,114 +166,259 @@ struct Args {
     force: bool,
 }
 
-fn main() -> Result<()> {
-    let world = scene_3::two_perlin_spheres();
+#[derive(Clone, Copy, clap::ValueEnum)]
+enum SceneName {
+    RandomSpheres,
+    TwoSpheres,
+    TwoPerlinSpheres,
+    Turntable,
+    CornellBox,
+    VeachMis,
+    AnalyticLights,
+    SunAndSky,
+    Principled,
+    Layered,
+    Dispersion,
+    Iridescence,
+    Subsurface,
+    Bumps,
+    Cutout,
+    Compositing,
+}
 
-    let Args {
-        image_width,
-        aspect_ratio,
-        samples_per_pixel,
-        max_depth,
-        output_path,
-        force,
-    } = Args::parse();
-    if let Some(ref path) = output_path {
-        OpenOptions::new()
-            .write(true)
-            .create_new(!force)
-            .create(force)
-            .open(path)?;
+impl SceneName {
+    fn setup(
+        self,
+        environment: Option<EnvironmentMap>,
+        projection: Option<Projection>,
+        fov: Option<f64>,
+    ) -> Scene {
+        let mut scene = match self {
+            Self::RandomSpheres => scene_1::setup(),
+            Self::TwoSpheres => scene_2::two_spheres(),
+            Self::TwoPerlinSpheres => scene_3::two_perlin_spheres(),
+            Self::Turntable => scene_4::turntable(),
+            Self::CornellBox => scene_5::cornell_box(),
+            Self::VeachMis => scene_6::veach_mis(),
+            Self::AnalyticLights => scene_7::analytic_lights(),
+            Self::SunAndSky => scene_8::sun_and_sky(),
+            Self::Principled => scene_9::principled(),
+            Self::Layered => scene_10::layered(),
+            Self::Dispersion => scene_11::dispersion(),
+            Self::Iridescence => scene_12::iridescence(),
+            Self::Subsurface => scene_13::subsurface(),
+            Self::Bumps => scene_14::bumps(),
+            Self::Cutout => scene_15::cutout(),
+            Self::Compositing => scene_16::compositing(),
+        };
+        if let Some(projection) = projection {
+            scene.camera.projection = projection;
+        }
+        if let Some(fov) = fov {
+            scene.camera.v_fov = fov;
+        }
+
+        match environment {
+            Some(environment) => scene.with_environment(environment),
+            None => scene,
+        }
     }
+}
 
-    let image_width_f = f64::from(image_width);
-    let image_height_f = if (image_width_f / aspect_ratio).fract() < f64::EPSILON {
-        image_width_f / aspect_ratio
+#[derive(Clone, Copy, clap::ValueEnum)]
+enum IntegratorName {
+    /// Path tracing.
+    Path,
+    /// Bidirectional path tracing.
+    Bdpt,
+    /// Stochastic progressive photon mapping, with one iteration per sample.
+    Sppm,
+    /// Primary sample space Metropolis light transport, with one mutation per sample.
+    Mlt,
+    /// Surface normals.
+    Normals,
+    /// Texture coordinates.
+    Uv,
+    /// Distance from the camera.
+    Depth,
+    /// Ambient occlusion.
+    Ao,
+    /// Number of BVH nodes visited per camera ray.
+    BvhCost,
+    /// A color per material.
+    MaterialId,
+    /// A color per object.
+    ObjectId,
+}
+
+#[derive(Clone, Copy, clap::ValueEnum)]
+enum ProjectionName {
+    /// A pinhole or thin lens.
+    Perspective,
+    /// Parallel rays, without perspective.
+    Orthographic,
+    /// A fisheye lens with equal angles at equal distances from the center.
+    EquidistantFisheye,
+    /// A fisheye lens with equal solid angles in equal areas.
+    EquisolidFisheye,
+    /// Every direction, for 360° panoramas at an aspect ratio of 2.
+    Equirectangular,
+    /// Every direction, as six cube faces at an aspect ratio of 1.5.
+    CubeMap,
+}
+
+/// Parse a frame range such as `0..120` (exclusive) or `0..=119` (inclusive).
+fn parse_frames(s: &str) -> Result<Range<u32>> {
+    let range = if let Some((start, end)) = s.split_once("..=") {
+        let Some(end) = end.parse::<u32>()?.checked_add(1) else {
+            bail!(
+                "frame range ends after the last frame number, {}",
+                u32::MAX - 1
+            );
+        };
+        start.parse()?..end
+    } else if let Some((start, end)) = s.split_once("..") {
+        start.parse()?..end.parse()?
     } else {
-        panic!("Error: image_width {image_width} is not valid for aspect_ratio {aspect_ratio}");
+        bail!("expected a range such as `0..120`");
     };
-    let image_height = image_height_f as u32;
-
-    let camera = Camera::new(
-        Point::newi(13, 2, 3),
-        Point::newi(0, 0, 0),
-        Vec3::newi(0, 1, 0),
-        20.0,
-        aspect_ratio,
-        0.1,
-        10.0,
-        0.0,
-        1.0,
-    );
 
-    let bar = ProgressBar::new(u64::from(image_height));
-    let timer = Instant::now();
+    if range.is_empty() {
+        bail!("frame range is empty");
+    }
+    Ok(range)
+}
 
-    let pixels = (0..image_height * image_width)
-        .into_par_iter()
-        .map(|i| (i % image_width, image_height - i / image_width - 1))
-        .map(|(x, y)| {
-            if x == 0 {
-                bar.inc(1);
-            }
+/// Substitute `frame` for a `%d` or zero-padded `%0Nd` pattern in `pattern`.
+fn frame_path(pattern: &str, frame: u32) -> Result<PathBuf> {
+    let Some(start) = pattern.find('%') else {
+        bail!("Error: output path {pattern} has no frame number pattern such as `%04d`");
+    };
+    let Some(len) = pattern[start..].find('d') else {
+        bail!("Error: output path {pattern} has an unterminated frame number pattern");
+    };
+    let width = match &pattern[start + 1..start + len] {
+        "" => 0,
+        spec if spec.starts_with('0') => spec.parse::<usize>()?,
+        spec => bail!("Error: unsupported frame number pattern `%{spec}d`"),
+    };
 
-            let mut rng = rand::rng();
-            let mut pixel = Rgb::ZERO;
+    Ok(PathBuf::from(format!(
+        "{}{frame:0width$}{}",
+        &pattern[..start],
+        &pattern[start + len + 1..]
+    )))
+}
 
-            for _ in 0..samples_per_pixel {
-                let u = (f64::from(x) + CLOSED_OPEN_01.sample(&mut rng)) / (image_width_f - 1.0);
-                let v = (f64::from(y) + CLOSED_OPEN_01.sample(&mut rng)) / (image_height_f - 1.0);
+/// Render the image with `integrator`, with the opacity of each pixel if `transparent` or
+/// opaque otherwise.
+fn render(
+    integrator: &dyn Integrator,
+    transparent: bool,
+    scene: &Scene,
+    camera: &Camera,
+    film: &Film,
+    bar: &ProgressBar,
+) -> Vec<Rgba> {
+    if transparent {
+        return integrator.render_matte(scene, camera, film, bar);
+    }
 
-                let r = camera.ray(u, v, &mut rng);
-                pixel += r.color(&world, max_depth, &mut rng);
-            }
+    let opaque = f64::from(film.samples_per_pixel);
+    integrator
+        .render(scene, camera, film, bar)
+        .into_iter()
+        .map(|color| Rgba::new(color, opaque))
+        .collect()
+}
 
-            pixel
-        })
-        .collect::<Vec<Rgb>>(); // TODO: avoid intermediate allocation
-    // may require a parallel to sequential adapter
-    // https://github.com/rayon-rs/rayon/issues/210
+/// An image buffer the size of the `film` holding `data`.
+fn buffer<P: Pixel>(
+    film: &Film,
+    data: Vec<P::Subpixel>,
+) -> Result<ImageBuffer<P, Vec<P::Subpixel>>> {
+    ImageBuffer::from_raw(film.image_width, film.image_height, data)
+        .context("Error: couldn't create image buffer")
+}
 
-    bar.finish_and_clear();
-    let bar = ProgressBar::new_spinner().with_message(format!(
-        "Writing to {}...",
-        output_path.as_ref().map_or("stdout", |path| path)
-    ));
+/// Write the image to the specified output, or to stdout. PNG and EXR images get an alpha channel
+/// if `transparent`.
+fn write(
+    film: &Film,
+    pixels: &[Rgba],
+    transparent: bool,
+    output_path: Option<&Path>,
+    force: bool,
+) -> Result<()> {
+    let Film {
+        image_width,
+        image_height,
+        samples_per_pixel,
+        ..
+    } = *film;
 
     // Write the image to a PPM file.
     let write_ppm = |buf: &mut dyn Write| -> Result<()> {
         writeln!(buf, "P3\n{image_width} {image_height}\n255")?;
-        for pixel in &pixels {
-            pixel.write(buf, samples_per_pixel)?;
+        for pixel in pixels {
+            pixel.color.write(buf, samples_per_pixel)?;
         }
         buf.flush()?;
         Ok(())
     };
+    let has_extension = |path: &Path, extension: &str| {
+        path.extension()
+            .is_some_and(|ext| ext.eq_ignore_ascii_case(extension))
+    };
 
-    // Write the image to the specified output.
-    if let Some(ref path) = output_path {
-        let path = Path::new(path);
-        if path
-            .extension()
-            .is_some_and(|ext| ext.eq_ignore_ascii_case("png"))
-        {
+    if let Some(path) = output_path {
+        let exr = has_extension(path, "exr");
+        if exr || has_extension(path, "png") {
             if force && path.exists() {
                 fs::remove_file(path)?;
             }
-            let Some(buf) = ImageBuffer::<image::Rgb<u8>, _>::from_raw(
-                image_width,
-                image_height,
-                pixels
-                    .iter()
-                    .flat_map(|p| p.to_rgb8(samples_per_pixel))
-                    .collect::<Box<[_]>>(),
-            ) else {
-                bail!("Error: couldn't create image buffer");
+            // OpenEXR keeps linear, unbounded colors, premultiplied by the opacity.
+            let image = match (exr, transparent) {
+                (false, false) => DynamicImage::ImageRgb8(buffer(
+                    film,
+                    pixels
+                        .iter()
+                        .flat_map(|p| p.color.to_rgb8(samples_per_pixel))
+                        .collect(),
+                )?),
+                (false, true) => DynamicImage::ImageRgba8(buffer(
+                    film,
+                    pixels
+                        .iter()
+                        .flat_map(|p| p.to_rgba8(samples_per_pixel))
+                        .collect(),
+                )?),
+                (true, false) => DynamicImage::ImageRgb32F(buffer(
+                    film,
+                    pixels
+                        .iter()
+                        .flat_map(|p| {
+                            let [r, g, b, _] = p.to_rgba32f(samples_per_pixel);
+                            [r, g, b]
+                        })
+                        .collect(),
+                )?),
+                (true, true) => DynamicImage::ImageRgba32F(buffer(
+                    film,
+                    pixels
+                        .iter()
+                        .flat_map(|p| p.to_rgba32f(samples_per_pixel))
+                        .collect(),
+                )?),
             };
-            buf.save(path)?;
+            image.save(path)?;
         } else {
-            let file = OpenOptions::new().write(true).truncate(force).open(path)?;
-            let mut buf = BufWriter::new(file);
-            write_ppm(&mut buf)?;
+            let file = OpenOptions::new()
+                .write(true)
+                .create(true)
+                .truncate(true)
+                .open(path)?;
+            write_ppm(&mut BufWriter::new(file))?;
         }
     } else {
         let stdout = io::stdout();
@@ -168,6 +427,173 @@ fn main() -> Result<()> {
         write_ppm(&mut buf)?;
     }
 
+    Ok(())
+}
+
+fn main() -> Result<()> {
+    let Args {
+        image_width,
+        aspect_ratio,
+        samples_per_pixel,
+        max_depth,
+        min_bounces,
+        clamp,
+        spectral,
+        transparent,
+        integrator,
+        photons,
+        photon_radius,
+        bootstrap,
+        chains,
+        mutation_sigma,
+        large_step,
+        ao_radius,
+        far,
+        scene,
+        projection,
+        fov,
+        environment,
+        environment_rotation,
+        environment_intensity,
+        frames,
+        fps,
+        shutter,
+        output_path,
+        force,
+    } = Args::parse();
+
+    let image_width_f = f64::from(image_width);
+    let image_height_f = if (image_width_f / aspect_ratio).fract() < f64::EPSILON {
+        image_width_f / aspect_ratio
+    } else {
+        panic!("Error: image_width {image_width} is not valid for aspect_ratio {aspect_ratio}");
+    };
+    let image_height = image_height_f as u32;
+
+    let film = Film::new(image_width, image_height, samples_per_pixel);
+    if spectral && !matches!(integrator, IntegratorName::Path | IntegratorName::Mlt) {
+        bail!("Error: only the path and mlt integrators render spectra");
+    }
+    if transparent
+        && matches!(
+            integrator,
+            IntegratorName::Bdpt | IntegratorName::Sppm | IntegratorName::Mlt
+        )
+    {
+        bail!("Error: the bdpt, sppm and mlt integrators don't render transparency");
+    }
+    let bdpt = matches!(integrator, IntegratorName::Bdpt);
+    let path_tracer = PathTracer::new(max_depth, min_bounces, clamp).with_spectral(spectral);
+    let integrator: Box<dyn Integrator> = match integrator {
+        IntegratorName::Path => Box::new(path_tracer),
+        IntegratorName::Bdpt => Box::new(Bdpt::new(max_depth, min_bounces, clamp)),
+        IntegratorName::Sppm => Box::new(Sppm::new(max_depth, min_bounces, photons, photon_radius)),
+        IntegratorName::Mlt => Box::new(Mlt::new(
+            path_tracer,
+            bootstrap,
+            chains,
+            mutation_sigma,
+            large_step,
+        )),
+        IntegratorName::Normals => Box::new(Normals),
+        IntegratorName::Uv => Box::new(Uv),
+        IntegratorName::Depth => Box::new(Depth { far }),
+        IntegratorName::Ao => Box::new(AmbientOcclusion { radius: ao_radius }),
+        IntegratorName::BvhCost => Box::new(BvhCost { max_visits: 256 }),
+        IntegratorName::MaterialId => Box::new(FalseColor::Material),
+        IntegratorName::ObjectId => Box::new(FalseColor::Object),
+    };
+    let environment = environment
+        .map(|path| {
+            EnvironmentMap::open(&path)
+                .map(|map| {
+                    map.with_rotation(environment_rotation)
+                        .with_intensity(environment_intensity)
+                })
+                .with_context(|| format!("Error: couldn't open {}", path.display()))
+        })
+        .transpose()?;
+    let projection = projection.map(|projection| match projection {
+        ProjectionName::Perspective => Projection::Perspective,
+        ProjectionName::Orthographic => Projection::Orthographic,
+        ProjectionName::EquidistantFisheye => Projection::EquidistantFisheye,
+        ProjectionName::EquisolidFisheye => Projection::EquisolidFisheye,
+        ProjectionName::Equirectangular => Projection::Equirectangular,
+        ProjectionName::CubeMap => Projection::CubeMap,
+    });
+    let timer = Instant::now();
+
+    if let Some(frames) = frames {
+        let Some(ref pattern) = output_path else {
+            bail!("Error: a frame sequence requires an output path");
+        };
+        // Validate the pattern before building the scene.
+        frame_path(pattern, frames.start)?;
+        let mut scene = scene.setup(environment, projection, fov);
+        if bdpt && scene.camera.projection != Projection::Perspective {
+            bail!("Error: the bdpt integrator only renders the perspective projection");
+        }
+        let t_end = f64::from(frames.end) / fps;
+        scene.build_bvh(f64::from(frames.start) / fps, t_end, &mut rand::rng());
+
+        for frame in frames {
+            let path = frame_path(pattern, frame)?;
+            if !force && path.exists() {
+                continue;
+            }
+
+            let t_start = f64::from(frame) / fps;
+            let t_end = shutter.mul_add(fps.recip(), t_start);
+            let camera = scene.camera.camera(aspect_ratio, t_start, t_end);
+
+            let bar =
+                ProgressBar::new(u64::from(image_height)).with_message(format!("Frame {frame}"));
+            let pixels = render(&*integrator, transparent, &scene, &camera, &film, &bar);
+            bar.finish_and_clear();
+
+            write(&film, &pixels, transparent, Some(&path), true)
+                .with_context(|| format!("Error: couldn't write frame {frame}"))?;
+        }
+
+        let elapsed = HumanDuration(timer.elapsed());
+        ProgressBar::new_spinner().finish_with_message(format!("Done in {elapsed}"));
+
+        return Ok(());
+    }
+
+    let mut scene = scene.setup(environment, projection, fov);
+    if bdpt && scene.camera.projection != Projection::Perspective {
+        bail!("Error: the bdpt integrator only renders the perspective projection");
+    }
+    if let Some(ref path) = output_path {
+        OpenOptions::new()
+            .write(true)
+            .create_new(!force)
+            .create(force)
+            .truncate(force)
+            .open(path)?;
+    }
+
+    scene.build_bvh(0.0, 1.0, &mut rand::rng());
+    let camera = scene.camera.camera(aspect_ratio, 0.0, 1.0);
+
+    let bar = ProgressBar::new(u64::from(image_height));
+    let pixels = render(&*integrator, transparent, &scene, &camera, &film, &bar);
+
+    bar.finish_and_clear();
+    let bar = ProgressBar::new_spinner().with_message(format!(
+        "Writing to {}...",
+        output_path.as_ref().map_or("stdout", |path| path)
+    ));
+
+    write(
+        &film,
+        &pixels,
+        transparent,
+        output_path.as_ref().map(Path::new),
+        force,
+    )?;
+
     let elapsed = HumanDuration(timer.elapsed());
     bar.finish_with_message(format!("Done in {elapsed}"));
 
@@ -1,7 +1,13 @@
 //! Primitive types.
 
+mod onb;
+mod quat;
+mod transform;
 mod vec3;
 
+pub use onb::Onb;
+pub use quat::Quat;
+pub use transform::{Keyframe, Keyframes, Transform};
 pub use vec3::{Point, Rgb, Vec3};
 
 #[derive(Clone, Copy, strum::EnumIter)]
@@ -0,0 +1,54 @@
+use crate::math::Vec3;
+
+/// An orthonormal basis.
+#[non_exhaustive]
+#[derive(Clone, Copy)]
+pub struct Onb {
+    pub u: Vec3,
+    pub v: Vec3,
+    pub w: Vec3,
+}
+
+impl Onb {
+    /// A basis whose `w` axis points along `n`.
+    pub fn from_w(n: Vec3) -> Self {
+        let w = n.unit();
+        let a = if w.x.abs() > 0.9 {
+            Vec3::newi(0, 1, 0)
+        } else {
+            Vec3::newi(1, 0, 0)
+        };
+        let v = w.cross(a).unit();
+        let u = w.cross(v);
+
+        Self { u, v, w }
+    }
+
+    /// A basis whose `w` axis points along `n` and whose `u` axis points along the part of
+    /// `tangent` across `n`, or [`Onb::from_w`] if `tangent` is zero or parallel to `n`.
+    pub fn from_w_tangent(n: Vec3, tangent: Vec3) -> Self {
+        let w = n.unit();
+        let u = w.mul_add(-w.dot(tangent), tangent);
+        if u.len_squared() <= 1.0e-12 * tangent.len_squared() {
+            return Self::from_w(n);
+        }
+        let u = u.unit();
+
+        Self {
+            u,
+            v: w.cross(u),
+            w,
+        }
+    }
+
+    /// Transform a vector from basis coordinates to world coordinates.
+    pub fn local(self, a: Vec3) -> Vec3 {
+        // a.x * u + a.y * v + a.z * w
+        self.u.mul_add(a.x, self.v.mul_add(a.y, a.z * self.w))
+    }
+
+    /// Transform a vector from world coordinates to basis coordinates.
+    pub fn to_local(self, a: Vec3) -> Vec3 {
+        Vec3::newf(a.dot(self.u), a.dot(self.v), a.dot(self.w))
+    }
+}
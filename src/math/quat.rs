@@ -0,0 +1,121 @@
+use std::ops;
+
+use crate::math::Vec3;
+
+/// A unit quaternion representing a rotation in 3D space.
+#[non_exhaustive]
+#[derive(Clone, Copy)]
+pub struct Quat {
+    pub w: f64,
+    pub x: f64,
+    pub y: f64,
+    pub z: f64,
+}
+
+impl Quat {
+    /// The identity rotation.
+    pub const IDENTITY: Self = Self::new(1.0, 0.0, 0.0, 0.0);
+
+    pub const fn new(w: f64, x: f64, y: f64, z: f64) -> Self {
+        Self { w, x, y, z }
+    }
+
+    /// A rotation of `degrees` around `axis`.
+    pub fn from_axis_angle(axis: Vec3, degrees: f64) -> Self {
+        let half = degrees.to_radians() / 2.0;
+        let (sin, cos) = half.sin_cos();
+        let axis = axis.unit() * sin;
+
+        Self::new(cos, axis.x, axis.y, axis.z)
+    }
+
+    /// Dot product.
+    pub fn dot(self, rhs: Self) -> f64 {
+        self.w.mul_add(
+            rhs.w,
+            self.x.mul_add(rhs.x, self.y.mul_add(rhs.y, self.z * rhs.z)),
+        )
+    }
+
+    /// The quaternion's unit quaternion.
+    pub fn unit(self) -> Self {
+        let len = self.dot(self).sqrt();
+        Self::new(self.w / len, self.x / len, self.y / len, self.z / len)
+    }
+
+    /// The conjugate, which is also the inverse rotation of a unit quaternion.
+    pub const fn conjugate(self) -> Self {
+        Self::new(self.w, -self.x, -self.y, -self.z)
+    }
+
+    /// Rotate a vector by the quaternion.
+    pub fn rotate(self, v: Vec3) -> Vec3 {
+        // v + 2w(q × v) + 2q × (q × v)
+        let q = Vec3::newf(self.x, self.y, self.z);
+        let t = 2.0 * q.cross(v);
+        t.mul_add(self.w, v) + q.cross(t)
+    }
+
+    /// Spherical linear interpolation between `self` and `other` by `t` in [0, 1], along the
+    /// shortest arc.
+    pub fn slerp(self, other: Self, t: f64) -> Self {
+        let mut cos = self.dot(other);
+        let other = if cos < 0.0 {
+            cos = -cos;
+            Self::new(-other.w, -other.x, -other.y, -other.z)
+        } else {
+            other
+        };
+
+        // Nearly parallel quaternions are linearly interpolated to avoid dividing by sin(~0).
+        let (a, b) = if cos > 0.9995 {
+            (1.0 - t, t)
+        } else {
+            let theta = cos.acos();
+            let sin = theta.sin();
+            (((1.0 - t) * theta).sin() / sin, (t * theta).sin() / sin)
+        };
+
+        Self::new(
+            a.mul_add(self.w, b * other.w),
+            a.mul_add(self.x, b * other.x),
+            a.mul_add(self.y, b * other.y),
+            a.mul_add(self.z, b * other.z),
+        )
+        .unit()
+    }
+}
+
+impl Default for Quat {
+    fn default() -> Self {
+        Self::IDENTITY
+    }
+}
+
+impl ops::Mul for Quat {
+    type Output = Self;
+
+    /// Hamilton product; the result applies `rhs` first, then `self`.
+    fn mul(self, rhs: Self) -> Self {
+        Self {
+            w: self.w.mul_add(
+                rhs.w,
+                -self.x.mul_add(rhs.x, self.y.mul_add(rhs.y, self.z * rhs.z)),
+            ),
+            x: self.w.mul_add(
+                rhs.x,
+                self.x
+                    .mul_add(rhs.w, self.y.mul_add(rhs.z, -(self.z * rhs.y))),
+            ),
+            y: self.w.mul_add(
+                rhs.y,
+                (-self.x).mul_add(rhs.z, self.y.mul_add(rhs.w, self.z * rhs.x)),
+            ),
+            z: self.w.mul_add(
+                rhs.z,
+                self.x
+                    .mul_add(rhs.y, (-self.y).mul_add(rhs.x, self.z * rhs.w)),
+            ),
+        }
+    }
+}
@@ -0,0 +1,147 @@
+use crate::math::{Point, Quat, Vec3};
+
+/// An affine transform composed of a scale, then a rotation, then a translation.
+#[non_exhaustive]
+#[derive(Clone, Copy)]
+pub struct Transform {
+    pub translation: Vec3,
+    pub rotation: Quat,
+    pub scale: Vec3,
+}
+
+impl Transform {
+    /// The identity transform.
+    pub const IDENTITY: Self = Self::new(Vec3::ZERO, Quat::IDENTITY, Vec3::ONE);
+
+    pub const fn new(translation: Vec3, rotation: Quat, scale: Vec3) -> Self {
+        Self {
+            translation,
+            rotation,
+            scale,
+        }
+    }
+
+    pub const fn from_translation(translation: Vec3) -> Self {
+        Self::new(translation, Quat::IDENTITY, Vec3::ONE)
+    }
+
+    pub const fn from_rotation(rotation: Quat) -> Self {
+        Self::new(Vec3::ZERO, rotation, Vec3::ONE)
+    }
+
+    pub const fn from_scale(scale: Vec3) -> Self {
+        Self::new(Vec3::ZERO, Quat::IDENTITY, scale)
+    }
+
+    /// Interpolate between `self` and `other` by `t` in [0, 1]. Translation and scale are
+    /// interpolated linearly and rotation spherically.
+    pub fn lerp(self, other: Self, t: f64) -> Self {
+        Self::new(
+            (other.translation - self.translation).mul_add(t, self.translation),
+            self.rotation.slerp(other.rotation, t),
+            (other.scale - self.scale).mul_add(t, self.scale),
+        )
+    }
+
+    /// Transform a point from object space to world space.
+    pub fn point(self, p: Point) -> Point {
+        self.rotation.rotate(p * self.scale) + self.translation
+    }
+
+    /// Transform a direction from object space to world space.
+    pub fn vector(self, v: Vec3) -> Vec3 {
+        self.rotation.rotate(v * self.scale)
+    }
+
+    /// Transform a surface normal from object space to world space. The result is not normalized.
+    pub fn normal(self, n: Vec3) -> Vec3 {
+        // Normals are transformed by the inverse transpose, which for a rotation and scale is the
+        // rotation and the inverse scale.
+        self.rotation.rotate(n / self.scale)
+    }
+
+    /// Transform a point from world space to object space.
+    pub fn inverse_point(self, p: Point) -> Point {
+        self.inverse_vector(p - self.translation)
+    }
+
+    /// Transform a direction from world space to object space.
+    pub fn inverse_vector(self, v: Vec3) -> Vec3 {
+        self.rotation.conjugate().rotate(v) / self.scale
+    }
+
+    /// The largest scale factor along any axis.
+    pub const fn max_scale(self) -> f64 {
+        self.scale
+            .x
+            .abs()
+            .max(self.scale.y.abs())
+            .max(self.scale.z.abs())
+    }
+}
+
+impl Default for Transform {
+    fn default() -> Self {
+        Self::IDENTITY
+    }
+}
+
+/// A [`Transform`] at a point in time.
+#[non_exhaustive]
+#[derive(Clone, Copy)]
+pub struct Keyframe {
+    pub time: f64,
+    pub transform: Transform,
+}
+
+impl Keyframe {
+    pub const fn new(time: f64, transform: Transform) -> Self {
+        Self { time, transform }
+    }
+}
+
+/// A sequence of [`Keyframe`]s ordered by time. Transforms before the first and after the last
+/// keyframe are held constant.
+#[derive(Clone)]
+pub struct Keyframes(Vec<Keyframe>);
+
+impl Keyframes {
+    /// # Panics
+    ///
+    /// If `keyframes` is empty or a keyframe time cannot be compared.
+    pub fn new(mut keyframes: Vec<Keyframe>) -> Self {
+        assert!(!keyframes.is_empty(), "No keyframes passed to animation");
+        keyframes.sort_by(|l, r| {
+            l.time
+                .partial_cmp(&r.time)
+                .expect("Keyframe times cannot be compared")
+        });
+
+        Self(keyframes)
+    }
+
+    /// A single keyframe holding `transform` for all time.
+    pub fn fixed(transform: Transform) -> Self {
+        Self(vec![Keyframe::new(0.0, transform)])
+    }
+
+    pub fn as_slice(&self) -> &[Keyframe] {
+        &self.0
+    }
+
+    /// The interpolated transform at time `t`.
+    pub fn at(&self, t: f64) -> Transform {
+        let frames = &self.0;
+        let i = frames.partition_point(|k| k.time <= t);
+
+        match i {
+            0 => frames[0].transform,
+            i if i == frames.len() => frames[i - 1].transform,
+            i => {
+                let (start, end) = (frames[i - 1], frames[i]);
+                let s = (t - start.time) / (end.time - start.time);
+                start.transform.lerp(end.transform, s)
+            }
+        }
+    }
+}
@@ -1,4 +1,4 @@
-use std::{io, io::Write, ops};
+use std::{f64::consts::PI, io, io::Write, ops};
 
 use rand::prelude::*;
 
@@ -9,7 +9,7 @@ use crate::{
 
 /// A vector in 3D Euclidean space (**R**³).
 #[non_exhaustive]
-#[derive(Clone, Copy)]
+#[derive(Clone, Copy, Default)]
 pub struct Vec3 {
     pub x: f64,
     pub y: f64,
@@ -70,6 +70,18 @@ impl Vec3 {
             z: self.x.mul_add(rhs.y, -(self.y * rhs.x)),
         }
     }
+    /// The largest component.
+    pub const fn max_component(self) -> f64 {
+        self.x.max(self.y).max(self.z)
+    }
+    /// The mean of the components.
+    pub const fn mean(self) -> f64 {
+        (self.x + self.y + self.z) / 3.0
+    }
+    /// The luminance of a linear sRGB color.
+    pub fn luminance(self) -> f64 {
+        0.2126f64.mul_add(self.x, 0.7152f64.mul_add(self.y, 0.0722 * self.z))
+    }
     /// The vector's unit vector.
     pub fn unit(self) -> Self {
         self / self.len()
@@ -136,7 +148,7 @@ impl Vec3 {
     }
 
     /// A random vector with components sampled from the uniform range [0, 1).
-    pub fn random(rng: &mut ThreadRng) -> Self {
+    pub fn random(rng: &mut dyn RngCore) -> Self {
         Self {
             x: CLOSED_OPEN_01.sample(rng),
             y: CLOSED_OPEN_01.sample(rng),
@@ -145,7 +157,7 @@ impl Vec3 {
     }
 
     /// A random vector with components sampled from the given distribution.
-    pub fn random_in(dist: &impl Distribution<f64>, rng: &mut ThreadRng) -> Self {
+    pub fn random_in(dist: &impl Distribution<f64>, rng: &mut dyn RngCore) -> Self {
         Self {
             x: dist.sample(rng),
             y: dist.sample(rng),
@@ -154,7 +166,7 @@ impl Vec3 {
     }
 
     /// A random vector within a unit sphere.
-    pub fn random_in_unit_sphere(rng: &mut ThreadRng) -> Self {
+    pub fn random_in_unit_sphere(rng: &mut dyn RngCore) -> Self {
         loop {
             let p = Self::random_in(&*CLOSED_OPEN_N11, rng);
             if p.len_squared() < 1.0 {
@@ -164,7 +176,7 @@ impl Vec3 {
     }
 
     /// A random vector within a unit disc.
-    pub fn random_in_unit_disc(rng: &mut ThreadRng) -> Self {
+    pub fn random_in_unit_disc(rng: &mut dyn RngCore) -> Self {
         loop {
             let p = Self::newf(
                 CLOSED_OPEN_N11.sample(rng),
@@ -178,12 +190,44 @@ impl Vec3 {
     }
 
     /// The unit vector of a random vector within a unit sphere.
-    pub fn random_unit_vec(rng: &mut ThreadRng) -> Self {
+    pub fn random_unit_vec(rng: &mut dyn RngCore) -> Self {
         Self::random_in_unit_sphere(rng).unit()
     }
 
+    /// A random unit vector in the hemisphere around +z, with a density proportional to the cosine
+    /// of its angle from +z.
+    pub fn random_cosine_direction(rng: &mut dyn RngCore) -> Self {
+        let r1 = CLOSED_OPEN_01.sample(rng);
+        let r2 = CLOSED_OPEN_01.sample(rng);
+        let phi = 2.0 * PI * r1;
+        let (sin, cos) = phi.sin_cos();
+        let r2_sqrt = r2.sqrt();
+
+        Self::newf(cos * r2_sqrt, sin * r2_sqrt, (1.0 - r2).sqrt())
+    }
+
+    /// A random unit vector around +z, uniformly distributed over the solid angle subtended by a
+    /// sphere of `radius` whose center is `distance_squared` away along +z.
+    pub fn random_to_sphere(radius: f64, distance_squared: f64, rng: &mut dyn RngCore) -> Self {
+        Self::random_in_cone((1.0 - radius * radius / distance_squared).sqrt(), rng)
+    }
+
+    /// A random unit vector uniformly distributed over the cone of directions whose angle from +z
+    /// has a cosine of at least `cos_theta_max`.
+    pub fn random_in_cone(cos_theta_max: f64, rng: &mut dyn RngCore) -> Self {
+        let r1 = CLOSED_OPEN_01.sample(rng);
+        let r2 = CLOSED_OPEN_01.sample(rng);
+        // 1 + r2 * (cos_theta_max - 1)
+        let z = r2.mul_add(cos_theta_max - 1.0, 1.0);
+        let phi = 2.0 * PI * r1;
+        let (sin, cos) = phi.sin_cos();
+        let sin_theta = z.mul_add(-z, 1.0).sqrt();
+
+        Self::newf(cos * sin_theta, sin * sin_theta, z)
+    }
+
     /// A random vector within the same hemisphere as the given `normal`.
-    pub fn random_in_hemisphere(normal: Self, rng: &mut ThreadRng) -> Self {
+    pub fn random_in_hemisphere(normal: Self, rng: &mut dyn RngCore) -> Self {
         let in_unit_sphere = Self::random_in_unit_sphere(rng);
 
         if in_unit_sphere.dot(normal) > 0.0 {
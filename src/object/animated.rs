@@ -0,0 +1,102 @@
+use crate::{
+    graphics::{Aabb, Hit, HitRecord, Ray},
+    math::{Keyframes, Point, Transform},
+};
+
+/// An object animated by a sequence of transform keyframes, evaluated at each [`Ray`]'s time.
+#[non_exhaustive]
+pub struct Animated {
+    object: Box<dyn Hit>,
+    keyframes: Keyframes,
+}
+
+impl Animated {
+    pub fn new(object: Box<dyn Hit>, keyframes: Keyframes) -> Self {
+        Self { object, keyframes }
+    }
+
+    /// A conservative box around `aabb` transformed by every transform interpolated between
+    /// `start` and `end`.
+    fn motion_bounds(aabb: Aabb, start: Transform, end: Transform) -> Aabb {
+        // Without a change in rotation, each transformed corner moves linearly between its
+        // transformed positions at `start` and `end`.
+        if start.rotation.dot(end.rotation).abs() > 1.0 - 1.0e-12 {
+            return Self::transformed_box(aabb, start)
+                .surrounding_box(Self::transformed_box(aabb, end));
+        }
+
+        // Otherwise, bound the rotating box by its circumscribed sphere around the object origin,
+        // whose center moves linearly with the translation.
+        let farthest = Point::newf(
+            aabb.min.x.abs().max(aabb.max.x.abs()),
+            aabb.min.y.abs().max(aabb.max.y.abs()),
+            aabb.min.z.abs().max(aabb.max.z.abs()),
+        );
+        let radius = farthest.len() * start.max_scale().max(end.max_scale());
+        let extent = Point::new_all(radius);
+        let box_start = Aabb::new(start.translation - extent, start.translation + extent);
+        let box_end = Aabb::new(end.translation - extent, end.translation + extent);
+
+        box_start.surrounding_box(box_end)
+    }
+
+    /// The box around the corners of `aabb` transformed by `transform`.
+    fn transformed_box(aabb: Aabb, transform: Transform) -> Aabb {
+        let mut min = Point::new_all(f64::INFINITY);
+        let mut max = Point::new_all(f64::NEG_INFINITY);
+
+        for i in 0..8 {
+            let corner = Point::newf(
+                if i & 1 == 0 { aabb.min.x } else { aabb.max.x },
+                if i & 2 == 0 { aabb.min.y } else { aabb.max.y },
+                if i & 4 == 0 { aabb.min.z } else { aabb.max.z },
+            );
+            let p = transform.point(corner);
+
+            min = Point::newf(min.x.min(p.x), min.y.min(p.y), min.z.min(p.z));
+            max = Point::newf(max.x.max(p.x), max.y.max(p.y), max.z.max(p.z));
+        }
+
+        Aabb::new(min, max)
+    }
+}
+
+impl Hit for Animated {
+    fn hit(&self, r: &Ray, t_min: f64, t_max: f64) -> Option<HitRecord<'_>> {
+        let transform = self.keyframes.at(r.t);
+        // The direction is not normalized, so `t` is the same in object and world space.
+        let local = Ray::new(
+            transform.inverse_point(r.origin),
+            transform.inverse_vector(r.direction),
+            r.t,
+        );
+
+        self.object.hit(&local, t_min, t_max).map(|mut rec| {
+            rec.point = transform.point(rec.point);
+            rec.normal = transform.normal(rec.normal).unit();
+            rec.dpdu = transform.vector(rec.dpdu);
+            rec.dpdv = transform.vector(rec.dpdv);
+            rec
+        })
+    }
+
+    fn bounding_box(&self, t_start: f64, t_end: f64) -> Option<Aabb> {
+        let aabb = self.object.bounding_box(t_start, t_end)?;
+
+        // Split the time range at each keyframe between `t_start` and `t_end`.
+        let mut times = vec![t_start];
+        times.extend(
+            self.keyframes
+                .as_slice()
+                .iter()
+                .map(|k| k.time)
+                .filter(|&t| t_start < t && t < t_end),
+        );
+        times.push(t_end);
+
+        times
+            .windows(2)
+            .map(|w| Self::motion_bounds(aabb, self.keyframes.at(w[0]), self.keyframes.at(w[1])))
+            .reduce(Aabb::surrounding_box)
+    }
+}
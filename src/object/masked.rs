@@ -0,0 +1,113 @@
+use std::sync::Arc;
+
+use rand::prelude::*;
+
+use crate::{
+    graphics::{Aabb, Hit, HitRecord, Ray, Texture},
+    math::{Point, Vec3},
+    rng::{self, CLOSED_OPEN_01},
+};
+
+/// An object with parts cut away by an alpha mask, like the outline of a leaf on a
+/// [`Quad`](crate::object::Quad).
+///
+/// The mask is read from the first channel of a texture: rays pass through where it is 0 and hit
+/// where it is 1. In between, rays pass through at random with the probability of transparency,
+/// decided by a hash of the ray and the intersection so that the same ray always sees the same
+/// surface.
+///
+/// Sampling the object as a light rejects points the same way, with the densities of the whole
+/// object, so directions sampled towards it may pass through its holes to whatever lies behind.
+#[non_exhaustive]
+pub struct Masked {
+    object: Box<dyn Hit>,
+    alpha: Arc<dyn Texture>,
+}
+
+impl Masked {
+    pub fn new(object: Box<dyn Hit>, alpha: Arc<dyn Texture>) -> Self {
+        Self { object, alpha }
+    }
+
+    /// Whether `r` hits the surface at `rec`, rather than passing through the mask.
+    fn opaque(&self, r: &Ray, rec: &HitRecord<'_>) -> bool {
+        let alpha = self.alpha.value(rec.u, rec.v, &rec.point).x;
+
+        alpha >= 1.0
+            || alpha > 0.0
+                && rng::hash_01(&[
+                    r.origin.x,
+                    r.origin.y,
+                    r.origin.z,
+                    r.direction.x,
+                    r.direction.y,
+                    r.direction.z,
+                    rec.t,
+                ]) < alpha
+    }
+}
+
+impl Hit for Masked {
+    fn hit(&self, r: &Ray, t_min: f64, t_max: f64) -> Option<HitRecord<'_>> {
+        let mut t_min = t_min;
+
+        loop {
+            let rec = self.object.hit(r, t_min, t_max)?;
+            if self.opaque(r, &rec) {
+                return Some(rec);
+            }
+
+            // Look for the next intersection behind the transparent one.
+            t_min = rec.t.next_up();
+        }
+    }
+
+    fn bounding_box(&self, t_start: f64, t_end: f64) -> Option<Aabb> {
+        self.object.bounding_box(t_start, t_end)
+    }
+
+    fn pdf_value(&self, origin: Point, direction: Vec3) -> f64 {
+        self.object.pdf_value(origin, direction)
+    }
+
+    fn random(&self, origin: Point, rng: &mut dyn RngCore) -> Vec3 {
+        self.object.random(origin, rng)
+    }
+
+    /// A point sampled on the object, or none if it was sampled where the mask lets light through.
+    fn sample_surface(&self, rng: &mut dyn RngCore) -> Option<(HitRecord<'_>, f64)> {
+        let (rec, pdf) = self.object.sample_surface(rng)?;
+        let alpha = self.alpha.value(rec.u, rec.v, &rec.point).x;
+
+        (alpha >= 1.0 || CLOSED_OPEN_01.sample(rng) < alpha).then_some((rec, pdf))
+    }
+
+    fn surface_pdf(&self, origin: Point, direction: Vec3) -> f64 {
+        if self
+            .hit(&Ray::new(origin, direction, 0.0), 0.001, f64::INFINITY)
+            .is_some()
+        {
+            self.object.surface_pdf(origin, direction)
+        } else {
+            0.0
+        }
+    }
+
+    fn hit_with_visits(&self, r: &Ray, t_min: f64, t_max: f64) -> (Option<HitRecord<'_>>, usize) {
+        let mut t_min = t_min;
+        let mut visits = 0;
+
+        loop {
+            let (rec, n) = self.object.hit_with_visits(r, t_min, t_max);
+            visits += n;
+            let Some(rec) = rec else {
+                return (None, visits);
+            };
+            if self.opaque(r, &rec) {
+                return (Some(rec), visits);
+            }
+
+            t_min = rec.t.next_up();
+        }
+    }
+}
@@ -1,5 +1,11 @@
 //! World objects.
 
+mod animated;
+mod masked;
+mod quad;
 mod sphere;
 
+pub use animated::Animated;
+pub use masked::Masked;
+pub use quad::{Quad, cuboid};
 pub use sphere::{MovingSphere, Sphere};
@@ -0,0 +1,164 @@
+use std::sync::Arc;
+
+use rand::prelude::*;
+
+use crate::{
+    graphics::{Aabb, Face, Hit, HitList, HitRecord, Material, Ray},
+    math::{Point, Vec3},
+    rng::CLOSED_OPEN_01,
+};
+
+/// A planar quadrilateral object spanned by two edge vectors from a corner.
+#[non_exhaustive]
+pub struct Quad {
+    q: Point,
+    u: Vec3,
+    v: Vec3,
+    /// The plane's normal scaled by the inverse of its squared length, used to find the planar
+    /// coordinates of a hit point.
+    w: Vec3,
+    normal: Vec3,
+    d: f64,
+    area: f64,
+    aabb: Aabb,
+    mat: Arc<dyn Material>,
+}
+
+impl Quad {
+    pub fn new(q: Point, u: Vec3, v: Vec3, mat: Arc<dyn Material>) -> Self {
+        // Pad the box so that a quad lying in an axis-aligned plane has a non-zero thickness.
+        const DELTA: f64 = 0.0001;
+
+        let n = u.cross(v);
+        let normal = n.unit();
+        let aabb = [q + u, q + v, q + u + v]
+            .into_iter()
+            .fold(Aabb::new(q, q), |aabb, p| {
+                aabb.surrounding_box(Aabb::new(p, p))
+            });
+        let aabb = Aabb::new(
+            aabb.min - Point::new_all(DELTA),
+            aabb.max + Point::new_all(DELTA),
+        );
+
+        Self {
+            q,
+            u,
+            v,
+            w: n / n.dot(n),
+            normal,
+            d: normal.dot(q),
+            area: n.len(),
+            aabb,
+            mat,
+        }
+    }
+}
+
+impl Hit for Quad {
+    fn hit(&self, r: &Ray, t_min: f64, t_max: f64) -> Option<HitRecord<'_>> {
+        let denom = self.normal.dot(r.direction);
+        // The ray is parallel to the plane.
+        if denom.abs() < 1.0e-8 {
+            return None;
+        }
+
+        let t = (self.d - self.normal.dot(r.origin)) / denom;
+        if t < t_min || t_max < t {
+            return None;
+        }
+
+        let point = r.at(t);
+        let planar = point - self.q;
+        let alpha = self.w.dot(planar.cross(self.v));
+        let beta = self.w.dot(self.u.cross(planar));
+        if !(0.0..=1.0).contains(&alpha) || !(0.0..=1.0).contains(&beta) {
+            return None;
+        }
+
+        let (face, normal) = HitRecord::face_normal(r, self.normal);
+
+        Some(
+            HitRecord::new(point, normal, &*self.mat, t, alpha, beta, face)
+                .with_tangents(self.u, self.v),
+        )
+    }
+
+    fn bounding_box(&self, _: f64, _: f64) -> Option<Aabb> {
+        Some(self.aabb)
+    }
+
+    /// The area density of [`Hit::random`] converted to solid angle at `origin`.
+    fn pdf_value(&self, origin: Point, direction: Vec3) -> f64 {
+        let Some(rec) = self.hit(&Ray::new(origin, direction, 0.0), 0.001, f64::INFINITY) else {
+            return 0.0;
+        };
+
+        let distance_squared = rec.t * rec.t * direction.len_squared();
+        let cosine = (direction.dot(rec.normal) / direction.len()).abs();
+
+        distance_squared / (cosine * self.area)
+    }
+
+    fn random(&self, origin: Point, rng: &mut dyn RngCore) -> Vec3 {
+        let p = self.u.mul_add(
+            CLOSED_OPEN_01.sample(rng),
+            self.v.mul_add(CLOSED_OPEN_01.sample(rng), self.q),
+        );
+
+        p - origin
+    }
+
+    fn sample_surface(&self, rng: &mut dyn RngCore) -> Option<(HitRecord<'_>, f64)> {
+        let (alpha, beta) = (CLOSED_OPEN_01.sample(rng), CLOSED_OPEN_01.sample(rng));
+        let point = self.u.mul_add(alpha, self.v.mul_add(beta, self.q));
+        let rec = HitRecord::new(
+            point,
+            self.normal,
+            &*self.mat,
+            0.0,
+            alpha,
+            beta,
+            Face::Front,
+        )
+        .with_tangents(self.u, self.v);
+
+        Some((rec, self.area.recip()))
+    }
+
+    fn surface_pdf(&self, origin: Point, direction: Vec3) -> f64 {
+        if self
+            .hit(&Ray::new(origin, direction, 0.0), 0.001, f64::INFINITY)
+            .is_some()
+        {
+            self.area.recip()
+        } else {
+            0.0
+        }
+    }
+}
+
+/// A box made of six [`Quad`]s, with opposite corners `a` and `b`.
+pub fn cuboid(a: Point, b: Point, mat: &Arc<dyn Material>) -> HitList {
+    let min = Point::newf(a.x.min(b.x), a.y.min(b.y), a.z.min(b.z));
+    let max = Point::newf(a.x.max(b.x), a.y.max(b.y), a.z.max(b.z));
+
+    let dx = Vec3::newf(max.x - min.x, 0.0, 0.0);
+    let dy = Vec3::newf(0.0, max.y - min.y, 0.0);
+    let dz = Vec3::newf(0.0, 0.0, max.z - min.z);
+
+    crate::hitlist![
+        // Front.
+        Quad::new(Point::newf(min.x, min.y, max.z), dx, dy, mat.clone()),
+        // Right.
+        Quad::new(Point::newf(max.x, min.y, max.z), -dz, dy, mat.clone()),
+        // Back.
+        Quad::new(Point::newf(max.x, min.y, min.z), -dx, dy, mat.clone()),
+        // Left.
+        Quad::new(Point::newf(min.x, min.y, min.z), dz, dy, mat.clone()),
+        // Top.
+        Quad::new(Point::newf(min.x, max.y, max.z), dx, -dz, mat.clone()),
+        // Bottom.
+        Quad::new(Point::newf(min.x, min.y, min.z), dx, dz, mat.clone()),
+    ]
+}
@@ -2,9 +2,11 @@
 
 use std::{f64::consts::PI, sync::Arc};
 
+use rand::prelude::*;
+
 use crate::{
-    graphics::{Aabb, Hit, HitRecord, Material, Ray},
-    math::Point,
+    graphics::{Aabb, Face, Hit, HitRecord, Material, Ray},
+    math::{Onb, Point, Vec3},
 };
 
 /// A sphere object.
@@ -30,6 +32,23 @@ impl Sphere {
 
         (phi / (2.0 * PI), theta / PI)
     }
+
+    /// The derivatives with respect to `u` and `v` of the point with the unit `outward_normal` on
+    /// a sphere of `radius`, following [`Sphere::uv`].
+    fn tangents(outward_normal: Vec3, u: f64, radius: f64) -> (Vec3, Vec3) {
+        let (sin_phi, cos_phi) = 2.0f64.mul_add(PI * u, -PI).sin_cos();
+        let cos_theta = -outward_normal.y;
+        let sin_theta = cos_theta.mul_add(-cos_theta, 1.0).max(0.0).sqrt();
+
+        (
+            2.0 * PI * radius * Vec3::newf(-sin_theta * sin_phi, 0.0, -sin_theta * cos_phi),
+            PI * radius * Vec3::newf(cos_theta * cos_phi, sin_theta, -cos_theta * sin_phi),
+        )
+    }
+
+    fn area(&self) -> f64 {
+        4.0 * PI * self.radius * self.radius
+    }
 }
 
 impl Hit for Sphere {
@@ -61,8 +80,9 @@ impl Hit for Sphere {
         let outward_normal = (p - self.center) / self.radius;
         let (face, normal) = HitRecord::face_normal(r, outward_normal);
         let (u, v) = Self::uv(&outward_normal);
+        let (dpdu, dpdv) = Self::tangents(outward_normal, u, self.radius);
 
-        Some(HitRecord::new(p, normal, &*self.mat, root, u, v, face))
+        Some(HitRecord::new(p, normal, &*self.mat, root, u, v, face).with_tangents(dpdu, dpdv))
     }
 
     fn bounding_box(&self, _: f64, _: f64) -> Option<Aabb> {
@@ -71,6 +91,64 @@ impl Hit for Sphere {
             self.center + Point::new_all(self.radius),
         ))
     }
+
+    /// The inverse of the solid angle subtended by the sphere, since [`Hit::random`] samples
+    /// that cone uniformly.
+    fn pdf_value(&self, origin: Point, direction: Vec3) -> f64 {
+        let distance_squared = (self.center - origin).len_squared();
+        // Directions from inside the sphere are not sampled.
+        if distance_squared <= self.radius * self.radius
+            || self
+                .hit(&Ray::new(origin, direction, 0.0), 0.001, f64::INFINITY)
+                .is_none()
+        {
+            return 0.0;
+        }
+
+        let cos_theta_max = (1.0 - self.radius * self.radius / distance_squared).sqrt();
+        let solid_angle = 2.0 * PI * (1.0 - cos_theta_max);
+
+        solid_angle.recip()
+    }
+
+    fn random(&self, origin: Point, rng: &mut dyn RngCore) -> Vec3 {
+        let direction = self.center - origin;
+        let distance_squared = direction.len_squared();
+        if distance_squared <= self.radius * self.radius {
+            return Vec3::random_unit_vec(rng);
+        }
+
+        Onb::from_w(direction).local(Vec3::random_to_sphere(self.radius, distance_squared, rng))
+    }
+
+    fn sample_surface(&self, rng: &mut dyn RngCore) -> Option<(HitRecord<'_>, f64)> {
+        let outward_normal = Vec3::random_unit_vec(rng);
+        let (u, v) = Self::uv(&outward_normal);
+        let (dpdu, dpdv) = Self::tangents(outward_normal, u, self.radius);
+        let rec = HitRecord::new(
+            outward_normal.mul_add(self.radius, self.center),
+            outward_normal,
+            &*self.mat,
+            0.0,
+            u,
+            v,
+            Face::Front,
+        )
+        .with_tangents(dpdu, dpdv);
+
+        Some((rec, self.area().recip()))
+    }
+
+    fn surface_pdf(&self, origin: Point, direction: Vec3) -> f64 {
+        if self
+            .hit(&Ray::new(origin, direction, 0.0), 0.001, f64::INFINITY)
+            .is_some()
+        {
+            self.area().recip()
+        } else {
+            0.0
+        }
+    }
 }
 
 /// A moving sphere object.
@@ -135,9 +213,10 @@ impl Hit for MovingSphere {
         let point = ray.at(root);
         let outward_normal = (point - self.center(ray.t)) / self.radius;
         let (u, v) = Sphere::uv(&outward_normal);
+        let (dpdu, dpdv) = Sphere::tangents(outward_normal, u, self.radius);
         let (face, normal) = HitRecord::face_normal(ray, outward_normal);
 
-        Some(HitRecord::new(point, normal, &*self.mat, root, u, v, face))
+        Some(HitRecord::new(point, normal, &*self.mat, root, u, v, face).with_tangents(dpdu, dpdv))
     }
 
     fn bounding_box(&self, t_start: f64, t_end: f64) -> Option<Aabb> {
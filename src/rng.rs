@@ -21,3 +21,24 @@ impl Distribution<Axis> for StandardUniform {
         }
     }
 }
+
+/// The SplitMix64 mix of `x`, which scrambles its bits so that nearby inputs give unrelated
+/// outputs.
+pub const fn splitmix64(x: u64) -> u64 {
+    let mut z = x.wrapping_add(0x9e37_79b9_7f4a_7c15);
+    z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+    z ^ (z >> 31)
+}
+
+/// A number in the range [0.0, 1.0) that is a deterministic hash of `values`, for random decisions
+/// that must be the same each time they are made.
+pub fn hash_01(values: &[f64]) -> f64 {
+    let hash = values
+        .iter()
+        .fold(0x9e37_79b9_7f4a_7c15_u64, |hash, value| {
+            splitmix64(hash ^ value.to_bits())
+        });
+
+    (hash >> 11) as f64 / (1u64 << 53) as f64
+}
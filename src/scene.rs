@@ -0,0 +1,76 @@
+//! Scene description.
+
+use std::mem;
+
+use rand::prelude::*;
+
+use crate::{
+    camera::CameraRig,
+    environment::{Environment, Gradient},
+    graphics::{Bvh, Hit, HitList},
+    light::Light,
+    math::Point,
+};
+
+/// The objects in a scene and the camera that views them.
+#[non_exhaustive]
+pub struct Scene {
+    pub world: HitList,
+    /// Emissive objects to sample directly at each diffuse bounce, and to trace light from. These
+    /// are usually copies of emissive objects in `world`, with the same materials.
+    pub lights: HitList,
+    /// Lights that are not objects in `world`, such as point lights, sampled with shadow rays.
+    pub analytic_lights: Vec<Box<dyn Light>>,
+    /// The light seen by rays that escape the scene.
+    pub environment: Box<dyn Environment>,
+    pub camera: CameraRig,
+}
+
+impl Scene {
+    pub fn new(world: HitList, camera: CameraRig) -> Self {
+        Self {
+            world,
+            lights: HitList::new(),
+            analytic_lights: Vec::new(),
+            environment: Box::new(Gradient::sky()),
+            camera,
+        }
+    }
+
+    #[must_use]
+    pub fn with_lights(mut self, lights: HitList) -> Self {
+        self.lights = lights;
+        self
+    }
+
+    #[must_use]
+    pub fn with_analytic_lights(mut self, analytic_lights: Vec<Box<dyn Light>>) -> Self {
+        self.analytic_lights = analytic_lights;
+        self
+    }
+
+    /// The center and radius of a sphere bounding the objects in the world at `time`, if any.
+    pub fn bounding_sphere(&self, time: f64) -> Option<(Point, f64)> {
+        let aabb = self.world.bounding_box(time, time)?;
+        Some((
+            0.5 * (aabb.min + aabb.max),
+            0.5 * (aabb.max - aabb.min).len(),
+        ))
+    }
+
+    /// Replace the objects in the world with a [`Bvh`] containing them, bounding their motion
+    /// from `t_start` to `t_end`.
+    pub fn build_bvh(&mut self, t_start: f64, t_end: f64, rng: &mut ThreadRng) {
+        if !self.world.is_empty() {
+            let objects = mem::take(&mut self.world);
+            self.world
+                .push(Box::new(Bvh::new(objects, t_start, t_end, rng)));
+        }
+    }
+
+    #[must_use]
+    pub fn with_environment(mut self, environment: impl Environment + 'static) -> Self {
+        self.environment = Box::new(environment);
+        self
+    }
+}
@@ -4,14 +4,15 @@ use std::sync::Arc;
 
 use rand::distr::{Distribution, Uniform};
 use sidewinder::{
+    camera::CameraRig,
     graphics::{Checkered, Dielectric, HitList, Lambertian, Metallic, Solid},
     math::{Point, Rgb, Vec3},
     object::{MovingSphere, Sphere},
     rng::CLOSED_OPEN_01,
+    scene::Scene,
 };
 
-#[allow(dead_code)]
-pub fn setup() -> HitList {
+pub fn setup() -> Scene {
     let textures = sidewinder::texlist![
         "ground": Checkered::from_colors(Rgb::newf(0.2, 0.3, 0.1), Rgb::new_all(0.9)),
         "lambertian": Solid::new(Rgb::newf(0.4, 0.2, 0.1)),
@@ -83,5 +84,15 @@ pub fn setup() -> HitList {
         }
     }
 
-    world
+    Scene::new(
+        world,
+        CameraRig::new(
+            Point::newi(13, 2, 3),
+            Point::newi(0, 0, 0),
+            Vec3::newi(0, 1, 0),
+            20.0,
+            0.1,
+            10.0,
+        ),
+    )
 }
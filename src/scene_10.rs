@@ -0,0 +1,52 @@
+//! Combined materials under a physical sky: lacquered red paint, clear-coated rough copper
+//! and a checkered mix of matte white and polished gold.
+
+use std::sync::Arc;
+
+use sidewinder::{
+    camera::CameraRig,
+    environment::PreethamSky,
+    graphics::{Checkered, Coated, Conductor, HitList, Lambertian, Material, Mix, Solid},
+    math::{Point, Rgb, Vec3},
+    object::Sphere,
+    scene::Scene,
+};
+
+pub fn layered() -> Scene {
+    let textures = sidewinder::texlist![
+        "ground": Solid::new(Rgb::new_all(0.4)),
+        "red": Solid::new(Rgb::newf(0.5, 0.1, 0.05)),
+        "white": Solid::new(Rgb::new_all(0.8)),
+        "checker": Checkered::from_colors(Rgb::ZERO, Rgb::ONE),
+    ];
+    let red: Arc<dyn Material> = Arc::new(Lambertian::new(textures["red"].clone()));
+    let white: Arc<dyn Material> = Arc::new(Lambertian::new(textures["white"].clone()));
+    let mats = sidewinder::matlist![
+        "ground": Lambertian::new(textures["ground"].clone()),
+        "lacquer": Coated::new(red, 1.5),
+        "copper": Coated::new(Arc::new(Conductor::copper(0.5)), 1.5),
+        "mix": Mix::new(white, Arc::new(Conductor::gold(0.0)), textures["checker"].clone()),
+    ];
+
+    let world = sidewinder::hitlist![
+        Sphere::new(Point::newi(0, -1000, 0), 1000.0, mats["ground"].clone()),
+        Sphere::new(Point::newi(-3, 1, 0), 1.0, mats["lacquer"].clone()),
+        Sphere::new(Point::newi(0, 1, 0), 1.0, mats["copper"].clone()),
+        Sphere::new(Point::newi(3, 1, 0), 1.0, mats["mix"].clone()),
+    ];
+    let sky = PreethamSky::new(35.0, 50.0, 3.0);
+
+    Scene::new(
+        world,
+        CameraRig::new(
+            Point::newi(0, 2, 12),
+            Point::newi(0, 1, 0),
+            Vec3::newi(0, 1, 0),
+            35.0,
+            0.0,
+            12.0,
+        ),
+    )
+    .with_analytic_lights(sidewinder::lightlist![sky.sun()])
+    .with_environment(sky)
+}
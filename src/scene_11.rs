@@ -0,0 +1,88 @@
+//! A white slit of light seen through a dense flint glass prism, which splits it into a spectrum
+//! when rendered with `--spectral`.
+
+use std::sync::Arc;
+
+use sidewinder::{
+    camera::CameraRig,
+    environment::Constant,
+    graphics::{Dielectric, DiffuseLight, HitList, Lambertian, Material, Solid},
+    math::{Point, Rgb, Vec3},
+    object::Quad,
+    scene::Scene,
+    spectrum::Dispersion,
+};
+
+/// The sides of a prism standing on the ground with an equilateral cross-section, turned by
+/// `angle` radians around the vertical axis. The ends are left open, out of view.
+fn prism(height: f64, angle: f64, mat: &Arc<dyn Material>) -> HitList {
+    let (sin, cos) = angle.sin_cos();
+    let corners = [(-1.0, 0.577), (1.0, 0.577), (0.0, -1.155)].map(|(x, z): (f64, f64)| {
+        Point::newf(cos.mul_add(x, -sin * z), 0.0, sin.mul_add(x, cos * z))
+    });
+    let up = Vec3::newf(0.0, height, 0.0);
+
+    let mut sides = HitList::default();
+    for (i, &a) in corners.iter().enumerate() {
+        let edge = corners[(i + 1) % 3] - a;
+        // Face outwards, away from the prism's axis.
+        let side = if up.cross(edge).dot(a) > 0.0 {
+            Quad::new(a, up, edge, mat.clone())
+        } else {
+            Quad::new(a, edge, up, mat.clone())
+        };
+        sides.push(Box::new(side));
+    }
+
+    sides
+}
+
+pub fn dispersion() -> Scene {
+    let textures = sidewinder::texlist![
+        "ground": Solid::new(Rgb::new_all(0.5)),
+        "light": Solid::new(Rgb::new_all(40.0)),
+    ];
+    let mats = sidewinder::matlist![
+        "ground": Lambertian::new(textures["ground"].clone()),
+        "flint": Dielectric::dispersive(Dispersion::SF11),
+        "light": DiffuseLight::new(textures["light"].clone()),
+    ];
+
+    // Light through the prism leaves about 70 degrees from the camera's view, so the slit stands
+    // out of view where that light goes, facing the prism.
+    let exit = Vec3::newf(0.945, 0.0, -0.327);
+    let across = Vec3::newf(-exit.z, 0.0, exit.x);
+    let slit = || {
+        Quad::new(
+            Point::newf(0.88, -1.5, -0.66) + 6.0 * exit - 0.05 * across,
+            0.1 * across,
+            Vec3::newf(0.0, 4.0, 0.0),
+            mats["light"].clone(),
+        )
+    };
+
+    let mut world = sidewinder::hitlist![
+        Quad::new(
+            Point::newf(-20.0, -1.5, -20.0),
+            Vec3::newf(0.0, 0.0, 40.0),
+            Vec3::newf(40.0, 0.0, 0.0),
+            mats["ground"].clone(),
+        ),
+        slit(),
+    ];
+    world.push(Box::new(prism(3.0, 55.0f64.to_radians(), &mats["flint"])));
+
+    Scene::new(
+        world,
+        CameraRig::new(
+            Point::newi(0, 0, 10),
+            Point::newi(0, 0, 0),
+            Vec3::newi(0, 1, 0),
+            20.0,
+            0.0,
+            10.0,
+        ),
+    )
+    .with_lights(sidewinder::hitlist![slit()])
+    .with_environment(Constant::new(Rgb::new_all(0.01)))
+}
@@ -0,0 +1,67 @@
+//! Thin-film interference under a physical sky: a soap bubble between spheres of titanium
+//! anodized blue and gold.
+
+use sidewinder::{
+    camera::CameraRig,
+    environment::PreethamSky,
+    graphics::{Conductor, Dielectric, HitList, Lambertian, Perlin, Solid, Texture, ThinFilm},
+    math::{Point, Rgb, Vec3},
+    object::Sphere,
+    scene::Scene,
+};
+
+/// The thickness of a soap film in nanometres, thinning towards the top of a bubble as it drains,
+/// with swirls of noise.
+struct Drainage {
+    center: Point,
+    radius: f64,
+    perlin: Perlin,
+}
+
+impl Texture for Drainage {
+    fn value(&self, _u: f64, _v: f64, p: &Point) -> Rgb {
+        let height = (p.y - self.center.y) / self.radius;
+        let swirl = self.perlin.noise(&(4.0 * *p));
+
+        Rgb::new_all(250.0f64.mul_add(swirl, 250.0f64.mul_add(-height, 450.0)))
+    }
+}
+
+pub fn iridescence() -> Scene {
+    let textures = sidewinder::texlist![
+        "ground": Solid::new(Rgb::new_all(0.4)),
+    ];
+    let bubble = Drainage {
+        center: Point::newi(0, 1, 0),
+        radius: 1.0,
+        perlin: Perlin::new(),
+    };
+    let mats = sidewinder::matlist![
+        "ground": Lambertian::new(textures["ground"].clone()),
+        "bubble": Dielectric::new(1.0).with_thin_film(ThinFilm::new(Arc::new(bubble), 1.33)),
+        "blue": Conductor::titanium(0.1).with_thin_film(ThinFilm::uniform(70.0, 2.4)),
+        "gold": Conductor::titanium(0.1).with_thin_film(ThinFilm::uniform(25.0, 2.4)),
+    ];
+
+    let world = sidewinder::hitlist![
+        Sphere::new(Point::newi(0, -1000, 0), 1000.0, mats["ground"].clone()),
+        Sphere::new(Point::newi(-3, 1, 0), 1.0, mats["blue"].clone()),
+        Sphere::new(Point::newi(0, 1, 0), 1.0, mats["bubble"].clone()),
+        Sphere::new(Point::newi(3, 1, 0), 1.0, mats["gold"].clone()),
+    ];
+    let sky = PreethamSky::new(35.0, 50.0, 3.0);
+
+    Scene::new(
+        world,
+        CameraRig::new(
+            Point::newi(0, 2, 12),
+            Point::newi(0, 1, 0),
+            Vec3::newi(0, 1, 0),
+            35.0,
+            0.0,
+            12.0,
+        ),
+    )
+    .with_analytic_lights(sidewinder::lightlist![sky.sun()])
+    .with_environment(sky)
+}
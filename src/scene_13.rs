@@ -0,0 +1,57 @@
+//! Subsurface scattering: spheres of wax, skin and marble lit from above and behind by a large
+//! light.
+
+use sidewinder::{
+    camera::CameraRig,
+    environment::Constant,
+    graphics::{DiffuseLight, HitList, Lambertian, Solid, Subsurface},
+    math::{Point, Rgb, Vec3},
+    object::{Quad, Sphere},
+    scene::Scene,
+};
+
+pub fn subsurface() -> Scene {
+    let textures = sidewinder::texlist![
+        "ground": Solid::new(Rgb::new_all(0.4)),
+        "light": Solid::new(Rgb::new_all(6.0)),
+    ];
+    let mats = sidewinder::matlist![
+        "ground": Lambertian::new(textures["ground"].clone()),
+        "wax": Subsurface::new(Rgb::newf(0.9, 0.75, 0.5), Rgb::newf(0.5, 0.4, 0.25), 1.45)
+            .with_roughness(0.3),
+        "skin": Subsurface::new(Rgb::newf(0.8, 0.5, 0.4), Rgb::newf(0.4, 0.15, 0.1), 1.4)
+            .with_roughness(0.4),
+        "marble": Subsurface::new(Rgb::newf(0.85, 0.83, 0.8), Rgb::new_all(0.1), 1.5),
+        "light": DiffuseLight::new(textures["light"].clone()),
+    ];
+    let light = || {
+        Quad::new(
+            Point::newi(-4, 7, -5),
+            Vec3::newi(8, 0, 0),
+            Vec3::newi(0, 0, 4),
+            mats["light"].clone(),
+        )
+    };
+
+    let world = sidewinder::hitlist![
+        Sphere::new(Point::newi(0, -1000, 0), 1000.0, mats["ground"].clone()),
+        Sphere::new(Point::newi(-3, 1, 0), 1.0, mats["wax"].clone()),
+        Sphere::new(Point::newi(0, 1, 0), 1.0, mats["skin"].clone()),
+        Sphere::new(Point::newi(3, 1, 0), 1.0, mats["marble"].clone()),
+        light(),
+    ];
+
+    Scene::new(
+        world,
+        CameraRig::new(
+            Point::newi(0, 2, 12),
+            Point::newi(0, 1, 0),
+            Vec3::newi(0, 1, 0),
+            35.0,
+            0.0,
+            12.0,
+        ),
+    )
+    .with_lights(sidewinder::hitlist![light()])
+    .with_environment(Constant::new(Rgb::new_all(0.05)))
+}
@@ -0,0 +1,119 @@
+//! Normal and bump mapping under a low sun: a lumpy stone sphere, a sphere of hammered gold from a
+//! procedural normal map, and a tiled floor.
+
+use sidewinder::{
+    camera::CameraRig,
+    environment::PreethamSky,
+    graphics::{Bumped, Conductor, HitList, Lambertian, Solid, Texture},
+    math::{Point, Rgb, Vec3},
+    object::{Quad, Sphere},
+    scene::Scene,
+};
+
+/// A tangent-space normal map of round dents in a grid, like hammered metal.
+struct Dents {
+    columns: f64,
+    rows: f64,
+}
+
+impl Texture for Dents {
+    fn value(&self, u: f64, v: f64, _p: &Point) -> Rgb {
+        const RADIUS: f64 = 0.45;
+
+        let dx = (u * self.columns).fract() - 0.5;
+        let dy = (v * self.rows).fract() - 0.5;
+        let r2 = dx.mul_add(dx, dy * dy);
+        // Inside a dent, the normal tilts towards its center like on the inside of a sphere.
+        let normal = if r2 < RADIUS * RADIUS {
+            Vec3::newf(-dx, -dy, RADIUS.mul_add(RADIUS, -r2).sqrt()).unit()
+        } else {
+            Vec3::newi(0, 0, 1)
+        };
+
+        normal.mul_add(0.5, Rgb::new_all(0.5))
+    }
+}
+
+/// A height of smooth lumps through space.
+struct Lumps {
+    frequency: f64,
+}
+
+impl Texture for Lumps {
+    fn value(&self, _u: f64, _v: f64, p: &Point) -> Rgb {
+        let p = self.frequency * *p;
+
+        Rgb::new_all(p.x.sin() * p.y.sin() * p.z.sin())
+    }
+}
+
+/// The height of square tiles separated by grooves, beveled at the edges.
+struct Tiles {
+    count: f64,
+}
+
+impl Texture for Tiles {
+    fn value(&self, u: f64, v: f64, _p: &Point) -> Rgb {
+        let edge = |t: f64| {
+            let t = (t * self.count).fract();
+            t.min(1.0 - t)
+        };
+        let distance = edge(u).min(edge(v));
+
+        Rgb::new_all(((distance - 0.02) / 0.06).clamp(0.0, 1.0))
+    }
+}
+
+pub fn bumps() -> Scene {
+    let textures = sidewinder::texlist![
+        "floor": Solid::new(Rgb::newf(0.6, 0.55, 0.5)),
+        "stone": Solid::new(Rgb::newf(0.45, 0.4, 0.35)),
+        "lumps": Lumps { frequency: 6.0 },
+        "dents": Dents {
+            columns: 48.0,
+            rows: 24.0,
+        },
+        "tiles": Tiles { count: 10.0 },
+    ];
+    let mats = sidewinder::matlist![
+        "floor": Bumped::bump(
+            Arc::new(Lambertian::new(textures["floor"].clone())),
+            textures["tiles"].clone(),
+            0.05,
+        ),
+        "stone": Bumped::bump(
+            Arc::new(Lambertian::new(textures["stone"].clone())),
+            textures["lumps"].clone(),
+            0.1,
+        ),
+        "gold": Bumped::normal_map(Arc::new(Conductor::gold(0.15)), textures["dents"].clone()),
+        "plain": Conductor::gold(0.15),
+    ];
+
+    let world = sidewinder::hitlist![
+        Quad::new(
+            Point::newi(-10, 0, -10),
+            Vec3::newi(0, 0, 20),
+            Vec3::newi(20, 0, 0),
+            mats["floor"].clone(),
+        ),
+        Sphere::new(Point::newi(-3, 1, 0), 1.0, mats["stone"].clone()),
+        Sphere::new(Point::newi(0, 1, 0), 1.0, mats["gold"].clone()),
+        Sphere::new(Point::newi(3, 1, 0), 1.0, mats["plain"].clone()),
+    ];
+    let sky = PreethamSky::new(20.0, 60.0, 3.0);
+
+    Scene::new(
+        world,
+        CameraRig::new(
+            Point::newi(0, 3, 12),
+            Point::newi(0, 1, 0),
+            Vec3::newi(0, 1, 0),
+            35.0,
+            0.0,
+            12.0,
+        ),
+    )
+    .with_analytic_lights(sidewinder::lightlist![sky.sun()])
+    .with_environment(sky)
+}
@@ -0,0 +1,116 @@
+//! Alpha masks under a low sun: leaves cut out of quads, a sphere cut into bands, and a sphere
+//! that is half transparent.
+
+use sidewinder::{
+    camera::CameraRig,
+    environment::PreethamSky,
+    graphics::{HitList, Lambertian, Solid, Texture},
+    math::{Point, Rgb, Vec3},
+    object::{Masked, Quad, Sphere},
+    scene::Scene,
+};
+
+/// The outline of a leaf on a quad, pointing towards increasing `v`, with holes eaten out of it.
+struct Leaf;
+
+impl Texture for Leaf {
+    fn value(&self, u: f64, v: f64, _p: &Point) -> Rgb {
+        let half_width = 0.4 * (std::f64::consts::PI * v).sin() * 0.5f64.mul_add(-v, 1.0);
+        let hole = (u - 0.6).hypot(v - 0.4) < 0.06 || (u - 0.35).hypot(v - 0.6) < 0.04;
+
+        if (u - 0.5).abs() < half_width && !hole {
+            Rgb::ONE
+        } else {
+            Rgb::ZERO
+        }
+    }
+}
+
+/// Opaque bands of latitude between transparent ones.
+struct Bands {
+    count: f64,
+}
+
+impl Texture for Bands {
+    fn value(&self, _u: f64, v: f64, _p: &Point) -> Rgb {
+        if (v * self.count).fract() < 0.5 {
+            Rgb::ONE
+        } else {
+            Rgb::ZERO
+        }
+    }
+}
+
+pub fn cutout() -> Scene {
+    let textures = sidewinder::texlist![
+        "ground": Solid::new(Rgb::new_all(0.5)),
+        "leaf": Solid::new(Rgb::newf(0.2, 0.5, 0.1)),
+        "red": Solid::new(Rgb::newf(0.7, 0.15, 0.1)),
+        "blue": Solid::new(Rgb::newf(0.1, 0.2, 0.7)),
+        "outline": Leaf,
+        "bands": Bands { count: 8.0 },
+        "half": Solid::new(Rgb::new_all(0.5)),
+    ];
+    let mats = sidewinder::matlist![
+        "ground": Lambertian::new(textures["ground"].clone()),
+        "leaf": Lambertian::new(textures["leaf"].clone()),
+        "red": Lambertian::new(textures["red"].clone()),
+        "blue": Lambertian::new(textures["blue"].clone()),
+    ];
+    let leaf = |corner: Point, across: Vec3, up: Vec3| {
+        Masked::new(
+            Box::new(Quad::new(corner, across, up, mats["leaf"].clone())),
+            textures["outline"].clone(),
+        )
+    };
+
+    let world = sidewinder::hitlist![
+        Sphere::new(Point::newi(0, -1000, 0), 1000.0, mats["ground"].clone()),
+        leaf(
+            Point::newf(-4.5, 0.0, 1.0),
+            Vec3::newf(2.0, 0.0, 0.5),
+            Vec3::newf(0.3, 3.0, -0.5),
+        ),
+        leaf(
+            Point::newf(-0.8, 0.0, 1.5),
+            Vec3::newf(1.6, 0.0, -0.3),
+            Vec3::newf(-0.4, 2.5, 0.4),
+        ),
+        leaf(
+            Point::newf(2.5, 0.0, 1.2),
+            Vec3::newf(1.8, 0.0, 0.2),
+            Vec3::newf(0.5, 2.8, -0.3),
+        ),
+        Masked::new(
+            Box::new(Sphere::new(
+                Point::newi(-2, 1, -2),
+                1.0,
+                mats["red"].clone(),
+            )),
+            textures["bands"].clone(),
+        ),
+        Masked::new(
+            Box::new(Sphere::new(
+                Point::newi(2, 1, -2),
+                1.0,
+                mats["blue"].clone(),
+            )),
+            textures["half"].clone(),
+        ),
+    ];
+    let sky = PreethamSky::new(30.0, 40.0, 3.0);
+
+    Scene::new(
+        world,
+        CameraRig::new(
+            Point::newi(0, 3, 12),
+            Point::newi(0, 1, 0),
+            Vec3::newi(0, 1, 0),
+            35.0,
+            0.0,
+            12.0,
+        ),
+    )
+    .with_analytic_lights(sidewinder::lightlist![sky.sun()])
+    .with_environment(sky)
+}
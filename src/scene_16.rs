@@ -0,0 +1,48 @@
+//! Compositing under a physical sky: a glass and a gold sphere on a shadow catcher, partly hidden
+//! behind a holdout standing in for an object in the background. Render with `--transparent`.
+
+use sidewinder::{
+    camera::CameraRig,
+    environment::PreethamSky,
+    graphics::{Conductor, Dielectric, HitList, Holdout, Lambertian, ShadowCatcher, Solid},
+    math::{Point, Rgb, Vec3},
+    object::Sphere,
+    scene::Scene,
+};
+
+pub fn compositing() -> Scene {
+    let textures = sidewinder::texlist![
+        "ground": Solid::new(Rgb::new_all(0.5)),
+        "red": Solid::new(Rgb::newf(0.7, 0.15, 0.1)),
+    ];
+    let mats = sidewinder::matlist![
+        "ground": ShadowCatcher::new(textures["ground"].clone()),
+        "holdout": Holdout,
+        "glass": Dielectric::new(1.5),
+        "gold": Conductor::gold(0.05),
+        "red": Lambertian::new(textures["red"].clone()),
+    ];
+
+    let world = sidewinder::hitlist![
+        Sphere::new(Point::newi(0, -1000, 0), 1000.0, mats["ground"].clone()),
+        Sphere::new(Point::newf(-1.5, 0.8, 2.0), 0.8, mats["holdout"].clone()),
+        Sphere::new(Point::newi(-3, 1, -1), 1.0, mats["glass"].clone()),
+        Sphere::new(Point::newi(0, 1, -1), 1.0, mats["gold"].clone()),
+        Sphere::new(Point::newi(3, 1, -1), 1.0, mats["red"].clone()),
+    ];
+    let sky = PreethamSky::new(35.0, 60.0, 3.0);
+
+    Scene::new(
+        world,
+        CameraRig::new(
+            Point::newi(0, 3, 12),
+            Point::newi(0, 1, 0),
+            Vec3::newi(0, 1, 0),
+            35.0,
+            0.0,
+            12.0,
+        ),
+    )
+    .with_analytic_lights(sidewinder::lightlist![sky.sun()])
+    .with_environment(sky)
+}
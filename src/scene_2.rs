@@ -3,21 +3,34 @@
 use std::sync::Arc;
 
 use sidewinder::{
+    camera::CameraRig,
     graphics::{Checkered, HitList, Lambertian},
-    math::{Point, Rgb},
+    math::{Point, Rgb, Vec3},
     object::Sphere,
+    scene::Scene,
 };
 
-#[allow(dead_code)]
-pub fn two_spheres() -> HitList {
+pub fn two_spheres() -> Scene {
     let checkered = Arc::new(Checkered::from_colors(
         Rgb::newf(0.2, 0.3, 0.1),
         Rgb::new_all(0.9),
     ));
     let mat = Arc::new(Lambertian::new(checkered));
 
-    sidewinder::hitlist![
+    let world = sidewinder::hitlist![
         Sphere::new(Point::newi(0, -10, 0), 10.0, mat.clone()),
         Sphere::new(Point::newi(0, 10, 0), 10.0, mat),
-    ]
+    ];
+
+    Scene::new(
+        world,
+        CameraRig::new(
+            Point::newi(13, 2, 3),
+            Point::newi(0, 0, 0),
+            Vec3::newi(0, 1, 0),
+            20.0,
+            0.1,
+            10.0,
+        ),
+    )
 }
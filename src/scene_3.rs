@@ -3,18 +3,31 @@
 use std::sync::Arc;
 
 use sidewinder::{
+    camera::CameraRig,
     graphics::{HitList, Lambertian, Noise, Perlin},
-    math::Point,
+    math::{Point, Vec3},
     object::Sphere,
+    scene::Scene,
 };
 
-#[allow(dead_code)]
-pub fn two_perlin_spheres() -> HitList {
+pub fn two_perlin_spheres() -> Scene {
     let tex = Arc::new(Noise::new(Perlin::new()));
     let mat = Arc::new(Lambertian::new(tex));
 
-    sidewinder::hitlist![
+    let world = sidewinder::hitlist![
         Sphere::new(Point::newi(0, -1000, 0), 1000.0, mat.clone()),
         Sphere::new(Point::newi(0, 2, 0), 2.0, mat),
-    ]
+    ];
+
+    Scene::new(
+        world,
+        CameraRig::new(
+            Point::newi(13, 2, 3),
+            Point::newi(0, 0, 0),
+            Vec3::newi(0, 1, 0),
+            20.0,
+            0.1,
+            10.0,
+        ),
+    )
 }
@@ -0,0 +1,68 @@
+//! Animated turntable scene.
+
+use sidewinder::{
+    camera::CameraRig,
+    graphics::{Checkered, Dielectric, HitList, Lambertian, Metallic, Solid},
+    math::{Keyframe, Keyframes, Point, Quat, Rgb, Transform, Vec3},
+    object::{Animated, Sphere},
+    scene::Scene,
+};
+
+/// Seconds for one full revolution of the turntable.
+const PERIOD: f64 = 5.0;
+
+pub fn turntable() -> Scene {
+    let textures = sidewinder::texlist![
+        "ground": Checkered::from_colors(Rgb::newf(0.2, 0.3, 0.1), Rgb::new_all(0.9)),
+        "lambertian": Solid::new(Rgb::newf(0.4, 0.2, 0.1)),
+    ];
+    let mats = sidewinder::matlist![
+        "ground": Lambertian::new(textures["ground"].clone()),
+        "dielectric": Dielectric::new(1.5),
+        "lambertian": Lambertian::new(textures["lambertian"].clone()),
+        "metallic": Metallic::new(Rgb::newf(0.7, 0.6, 0.5), 0.0),
+    ];
+    let platform = sidewinder::hitlist![
+        Sphere::new(Point::newi(0, 1, 0), 1.0, mats["dielectric"].clone()),
+        Sphere::new(Point::newi(-3, 1, 0), 1.0, mats["lambertian"].clone()),
+        Sphere::new(Point::newi(3, 1, 0), 1.0, mats["metallic"].clone()),
+    ];
+
+    // Quaternions interpolate along the shortest arc, so a full revolution needs keyframes less
+    // than 180 degrees apart.
+    let spin = Keyframes::new(
+        (0..=3)
+            .map(|i| {
+                let i = f64::from(i);
+                let rotation = Quat::from_axis_angle(Vec3::newi(0, 1, 0), 120.0 * i);
+                Keyframe::new(PERIOD * i / 3.0, Transform::from_rotation(rotation))
+            })
+            .collect(),
+    );
+    // Push the camera in over one revolution.
+    let dolly = Keyframes::new(vec![
+        Keyframe::new(0.0, Transform::IDENTITY),
+        Keyframe::new(
+            PERIOD,
+            Transform::from_translation(Vec3::newf(-3.25, -0.5, -0.75)),
+        ),
+    ]);
+
+    let world = sidewinder::hitlist![
+        Sphere::new(Point::newi(0, -1000, 0), 1000.0, mats["ground"].clone()),
+        Animated::new(Box::new(platform), spin),
+    ];
+
+    Scene::new(
+        world,
+        CameraRig::new(
+            Point::newi(13, 2, 3),
+            Point::newi(0, 0, 0),
+            Vec3::newi(0, 1, 0),
+            20.0,
+            0.0,
+            10.0,
+        )
+        .with_keyframes(dolly),
+    )
+}
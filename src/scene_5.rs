@@ -0,0 +1,100 @@
+//! Cornell box scene for book 3.
+
+use std::sync::Arc;
+
+use sidewinder::{
+    camera::CameraRig,
+    environment::Constant,
+    graphics::{Dielectric, DiffuseLight, HitList, Lambertian, Material, Solid},
+    math::{Keyframes, Point, Quat, Rgb, Transform, Vec3},
+    object::{Animated, Quad, Sphere, cuboid},
+    scene::Scene,
+};
+
+pub fn cornell_box() -> Scene {
+    let textures = sidewinder::texlist![
+        "red": Solid::new(Rgb::newf(0.65, 0.05, 0.05)),
+        "white": Solid::new(Rgb::new_all(0.73)),
+        "green": Solid::new(Rgb::newf(0.12, 0.45, 0.15)),
+        "light": Solid::new(Rgb::new_all(15.0)),
+    ];
+    let mats = sidewinder::matlist![
+        "red": Lambertian::new(textures["red"].clone()),
+        "white": Lambertian::new(textures["white"].clone()),
+        "green": Lambertian::new(textures["green"].clone()),
+        "light": DiffuseLight::new(textures["light"].clone()),
+        "glass": Dielectric::new(1.5),
+    ];
+
+    let light = |mat: Arc<dyn Material>| {
+        Quad::new(
+            Point::newi(343, 554, 332),
+            Vec3::newi(-130, 0, 0),
+            Vec3::newi(0, 0, -105),
+            mat,
+        )
+    };
+    let tall_box = Animated::new(
+        Box::new(cuboid(
+            Point::newi(0, 0, 0),
+            Point::newi(165, 330, 165),
+            &mats["white"],
+        )),
+        Keyframes::fixed(Transform::new(
+            Vec3::newi(265, 0, 295),
+            Quat::from_axis_angle(Vec3::newi(0, 1, 0), 15.0),
+            Vec3::ONE,
+        )),
+    );
+
+    let world = sidewinder::hitlist![
+        Quad::new(
+            Point::newi(555, 0, 0),
+            Vec3::newi(0, 555, 0),
+            Vec3::newi(0, 0, 555),
+            mats["green"].clone(),
+        ),
+        Quad::new(
+            Point::newi(0, 0, 0),
+            Vec3::newi(0, 555, 0),
+            Vec3::newi(0, 0, 555),
+            mats["red"].clone(),
+        ),
+        light(mats["light"].clone()),
+        Quad::new(
+            Point::newi(0, 0, 0),
+            Vec3::newi(555, 0, 0),
+            Vec3::newi(0, 0, 555),
+            mats["white"].clone(),
+        ),
+        Quad::new(
+            Point::newi(555, 555, 555),
+            Vec3::newi(-555, 0, 0),
+            Vec3::newi(0, 0, -555),
+            mats["white"].clone(),
+        ),
+        Quad::new(
+            Point::newi(0, 0, 555),
+            Vec3::newi(555, 0, 0),
+            Vec3::newi(0, 555, 0),
+            mats["white"].clone(),
+        ),
+        tall_box,
+        Sphere::new(Point::newi(190, 90, 190), 90.0, mats["glass"].clone()),
+    ];
+    let lights = sidewinder::hitlist![light(mats["light"].clone())];
+
+    Scene::new(
+        world,
+        CameraRig::new(
+            Point::newi(278, 278, -800),
+            Point::newi(278, 278, 0),
+            Vec3::newi(0, 1, 0),
+            40.0,
+            0.0,
+            10.0,
+        ),
+    )
+    .with_lights(lights)
+    .with_environment(Constant::new(Rgb::ZERO))
+}
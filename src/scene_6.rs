@@ -0,0 +1,80 @@
+//! Veach's multiple importance sampling test scene: glossy plates of increasing roughness
+//! reflecting spherical lights of decreasing size.
+
+use std::sync::Arc;
+
+use sidewinder::{
+    camera::CameraRig,
+    environment::Constant,
+    graphics::{DiffuseLight, HitList, Lambertian, Metallic, Solid},
+    math::{Point, Rgb, Vec3},
+    object::{Quad, Sphere},
+    scene::Scene,
+};
+
+pub fn veach_mis() -> Scene {
+    let mut world = HitList::new();
+    let mut lights = HitList::new();
+
+    // Each small light has the same power, so smaller lights are brighter. The last light is a
+    // fill light above the scene.
+    for (center, radius, radiance) in [
+        (Point::newf(-3.75, 0.0, 0.0), 0.033_333, 901.803),
+        (Point::newf(-1.25, 0.0, 0.0), 0.1, 100.0),
+        (Point::newf(1.25, 0.0, 0.0), 0.3, 11.1111),
+        (Point::newf(3.75, 0.0, 0.0), 0.9, 1.234_57),
+        (Point::newi(10, 10, 4), 0.5, 800.0),
+    ] {
+        let mat = Arc::new(DiffuseLight::new(Arc::new(Solid::new(Rgb::new_all(
+            radiance,
+        )))));
+
+        world.push(Box::new(Sphere::new(center, radius, mat.clone())));
+        lights.push(Box::new(Sphere::new(center, radius, mat)));
+    }
+
+    // Plates ordered from the back to the front, and from smooth to rough. Each plate is tilted so
+    // that its center reflects the row of lights towards the camera.
+    let from = Point::newi(0, 2, 15);
+    for (center, blur) in [
+        (Point::newf(0.0, -2.395, -0.135), 0.01),
+        (Point::newf(0.0, -2.957, 0.994), 0.04),
+        (Point::newf(0.0, -3.417, 2.312), 0.1),
+        (Point::newf(0.0, -3.860, 3.610), 0.25),
+    ] {
+        let normal = (from - center).unit() + (Point::ZERO - center).unit();
+        let edge = Vec3::newf(0.0, normal.z, -normal.y).unit();
+        let corner = center - Vec3::newi(4, 0, 0) - 0.5 * edge;
+
+        let mat = Arc::new(Metallic::new(Rgb::new_all(0.7), blur));
+        world.push(Box::new(Quad::new(corner, Vec3::newi(8, 0, 0), edge, mat)));
+    }
+
+    let backdrop = Arc::new(Lambertian::new(Arc::new(Solid::new(Rgb::new_all(0.4)))));
+    world.push(Box::new(Quad::new(
+        Point::newf(-10.0, -4.146_15, -10.0),
+        Vec3::newi(0, 0, 20),
+        Vec3::newi(20, 0, 0),
+        backdrop.clone(),
+    )));
+    world.push(Box::new(Quad::new(
+        Point::newf(-10.0, -4.146_15, -2.0),
+        Vec3::newi(20, 0, 0),
+        Vec3::newi(0, 20, 0),
+        backdrop,
+    )));
+
+    Scene::new(
+        world,
+        CameraRig::new(
+            from,
+            Point::newf(0.0, -2.0, 2.5),
+            Vec3::newi(0, 1, 0),
+            28.0,
+            0.0,
+            10.0,
+        ),
+    )
+    .with_lights(lights)
+    .with_environment(Constant::new(Rgb::ZERO))
+}
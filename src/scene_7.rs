@@ -0,0 +1,57 @@
+//! Analytic lights: a low sun, a point light and a spot light shining through green glass.
+
+use sidewinder::{
+    camera::CameraRig,
+    environment::Constant,
+    graphics::{Checkered, Dielectric, HitList, Lambertian, Metallic, Solid},
+    light::{DirectionalLight, PointLight, SpotLight},
+    math::{Point, Rgb, Vec3},
+    object::Sphere,
+    scene::Scene,
+};
+
+pub fn analytic_lights() -> Scene {
+    let textures = sidewinder::texlist![
+        "ground": Checkered::from_colors(Rgb::new_all(0.2), Rgb::new_all(0.8)),
+        "diffuse": Solid::new(Rgb::newf(0.7, 0.3, 0.2)),
+    ];
+    let mats = sidewinder::matlist![
+        "ground": Lambertian::new(textures["ground"].clone()),
+        "diffuse": Lambertian::new(textures["diffuse"].clone()),
+        "metal": Metallic::new(Rgb::newf(0.8, 0.8, 0.9), 0.05),
+        "glass": Dielectric::new(1.5).with_transmittance(Rgb::newf(0.3, 0.8, 0.4), 2.0),
+    ];
+
+    let world = sidewinder::hitlist![
+        Sphere::new(Point::newi(0, -1000, 0), 1000.0, mats["ground"].clone()),
+        Sphere::new(Point::newi(-3, 1, 0), 1.0, mats["diffuse"].clone()),
+        Sphere::new(Point::newi(0, 1, 0), 1.0, mats["glass"].clone()),
+        Sphere::new(Point::newi(3, 1, 0), 1.0, mats["metal"].clone()),
+    ];
+    let lights = sidewinder::lightlist![
+        DirectionalLight::new(Vec3::newf(-1.0, -0.6, -0.4), Rgb::newf(0.6, 0.5, 0.4))
+            .with_angular_diameter(2.0),
+        PointLight::new(Point::newf(-4.0, 3.0, 2.5), Rgb::newf(4.0, 6.0, 10.0)),
+        SpotLight::new(
+            Point::newi(0, 6, 0),
+            Point::newi(0, 0, 0),
+            Rgb::new_all(40.0),
+            8.0,
+            14.0,
+        ),
+    ];
+
+    Scene::new(
+        world,
+        CameraRig::new(
+            Point::newi(0, 4, 12),
+            Point::newi(0, 1, 0),
+            Vec3::newi(0, 1, 0),
+            30.0,
+            0.0,
+            12.0,
+        ),
+    )
+    .with_analytic_lights(lights)
+    .with_environment(Constant::new(Rgb::new_all(0.02)))
+}
@@ -0,0 +1,45 @@
+//! Spheres outdoors under a physical sky, lit by a low afternoon sun.
+
+use sidewinder::{
+    camera::CameraRig,
+    environment::PreethamSky,
+    graphics::{Conductor, Dielectric, HitList, Lambertian, Solid},
+    math::{Point, Rgb, Vec3},
+    object::Sphere,
+    scene::Scene,
+};
+
+pub fn sun_and_sky() -> Scene {
+    let textures = sidewinder::texlist![
+        "ground": Solid::new(Rgb::new_all(0.4)),
+        "white": Solid::new(Rgb::new_all(0.73)),
+    ];
+    let mats = sidewinder::matlist![
+        "ground": Lambertian::new(textures["ground"].clone()),
+        "white": Lambertian::new(textures["white"].clone()),
+        "gold": Conductor::gold(0.3),
+        "frosted": Dielectric::new(1.5).with_roughness(0.3),
+    ];
+
+    let world = sidewinder::hitlist![
+        Sphere::new(Point::newi(0, -1000, 0), 1000.0, mats["ground"].clone()),
+        Sphere::new(Point::newi(-3, 1, 0), 1.0, mats["white"].clone()),
+        Sphere::new(Point::newi(0, 1, 0), 1.0, mats["frosted"].clone()),
+        Sphere::new(Point::newi(3, 1, 0), 1.0, mats["gold"].clone()),
+    ];
+    let sky = PreethamSky::new(20.0, 60.0, 3.0);
+
+    Scene::new(
+        world,
+        CameraRig::new(
+            Point::newi(0, 2, 12),
+            Point::newi(0, 1, 0),
+            Vec3::newi(0, 1, 0),
+            35.0,
+            0.0,
+            12.0,
+        ),
+    )
+    .with_analytic_lights(sidewinder::lightlist![sky.sun()])
+    .with_environment(sky)
+}
@@ -0,0 +1,69 @@
+//! A row of spheres showing off the principled material under a physical sky: plastic, brushed
+//! copper, velvet, car paint and frosted tinted glass.
+
+use std::sync::Arc;
+
+use sidewinder::{
+    camera::CameraRig,
+    environment::PreethamSky,
+    graphics::{HitList, Lambertian, Principled, Solid, Texture},
+    math::{Point, Rgb, Vec3},
+    object::Sphere,
+    scene::Scene,
+};
+
+fn constant(n: f64) -> Arc<dyn Texture> {
+    Arc::new(Solid::new(Rgb::new_all(n)))
+}
+
+pub fn principled() -> Scene {
+    let textures = sidewinder::texlist![
+        "ground": Solid::new(Rgb::new_all(0.4)),
+        "red": Solid::new(Rgb::newf(0.8, 0.1, 0.1)),
+        "copper": Solid::new(Rgb::newf(0.95, 0.64, 0.54)),
+        "velvet": Solid::new(Rgb::newf(0.3, 0.05, 0.4)),
+        "paint": Solid::new(Rgb::newf(0.05, 0.2, 0.6)),
+        "glass": Solid::new(Rgb::newf(0.7, 0.9, 0.8)),
+    ];
+    let mats = sidewinder::matlist![
+        "ground": Lambertian::new(textures["ground"].clone()),
+        "plastic": Principled::new(textures["red"].clone()).with_roughness(constant(0.3)),
+        "copper": Principled::new(textures["copper"].clone())
+            .with_metallic(constant(1.0))
+            .with_roughness(constant(0.4)),
+        "velvet": Principled::new(textures["velvet"].clone())
+            .with_roughness(constant(0.9))
+            .with_sheen(constant(1.0), constant(0.5)),
+        "paint": Principled::new(textures["paint"].clone())
+            .with_metallic(constant(0.5))
+            .with_roughness(constant(0.5))
+            .with_clearcoat(constant(1.0), constant(0.9)),
+        "glass": Principled::new(textures["glass"].clone())
+            .with_roughness(constant(0.2))
+            .with_transmission(constant(1.0), constant(1.5)),
+    ];
+
+    let world = sidewinder::hitlist![
+        Sphere::new(Point::newi(0, -1000, 0), 1000.0, mats["ground"].clone()),
+        Sphere::new(Point::newf(-4.4, 1.0, 0.0), 1.0, mats["plastic"].clone()),
+        Sphere::new(Point::newf(-2.2, 1.0, 0.0), 1.0, mats["copper"].clone()),
+        Sphere::new(Point::newf(0.0, 1.0, 0.0), 1.0, mats["velvet"].clone()),
+        Sphere::new(Point::newf(2.2, 1.0, 0.0), 1.0, mats["paint"].clone()),
+        Sphere::new(Point::newf(4.4, 1.0, 0.0), 1.0, mats["glass"].clone()),
+    ];
+    let sky = PreethamSky::new(35.0, 50.0, 3.0);
+
+    Scene::new(
+        world,
+        CameraRig::new(
+            Point::newi(0, 3, 14),
+            Point::newi(0, 1, 0),
+            Vec3::newi(0, 1, 0),
+            35.0,
+            0.0,
+            14.0,
+        ),
+    )
+    .with_analytic_lights(sidewinder::lightlist![sky.sun()])
+    .with_environment(sky)
+}
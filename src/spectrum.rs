@@ -0,0 +1,273 @@
+//! Spectral rendering: wavelengths sampled per path, and conversions between colors and spectra.
+
+use std::sync::LazyLock;
+
+use rand::prelude::*;
+
+use crate::{
+    math::{Rgb, Vec3},
+    rng::CLOSED_OPEN_01,
+};
+
+/// The shortest visible wavelength sampled, in nanometres.
+pub const LAMBDA_MIN: f64 = 380.0;
+/// The longest visible wavelength sampled, in nanometres.
+pub const LAMBDA_MAX: f64 = 780.0;
+
+/// The wavelength of the sodium D line, in nanometres, where refractive indices are usually
+/// quoted.
+pub const LAMBDA_D: f64 = 589.3;
+
+/// The wavelengths of light carried along a path, one per component of the [`Vec3`]s that would
+/// otherwise hold RGB colors.
+///
+/// A hero wavelength is sampled uniformly over the visible spectrum, and the others are spaced
+/// evenly after it, wrapping around. Where the wavelengths would take different directions, such
+/// as refraction with dispersion, only the hero is followed. Without a spectrum, the components
+/// are the RGB channels.
+#[derive(Clone, Copy)]
+pub struct Wavelengths {
+    lambda: Option<[f64; 3]>,
+    /// Whether only the hero wavelength is still carried.
+    single: bool,
+}
+
+impl Wavelengths {
+    /// Colors carried as RGB rather than wavelengths.
+    pub const RGB: Self = Self {
+        lambda: None,
+        single: false,
+    };
+
+    pub fn sample(rng: &mut dyn RngCore) -> Self {
+        let range = LAMBDA_MAX - LAMBDA_MIN;
+        let hero = CLOSED_OPEN_01.sample(rng) * range;
+        let lambda = [0.0, 1.0, 2.0].map(|i| LAMBDA_MIN + (i * range / 3.0 + hero) % range);
+
+        Self {
+            lambda: Some(lambda),
+            single: false,
+        }
+    }
+
+    /// The hero wavelength, if any.
+    pub fn hero(self) -> Option<f64> {
+        self.lambda.map(|lambda| lambda[0])
+    }
+
+    /// Stop carrying all but the hero wavelength.
+    pub const fn terminate_secondary(&mut self) {
+        if self.lambda.is_some() {
+            self.single = true;
+        }
+    }
+
+    /// The values at each wavelength of a smooth spectrum with the given linear sRGB color.
+    pub fn uplift(self, color: Rgb) -> Vec3 {
+        let Some(lambda) = self.lambda else {
+            return color;
+        };
+        let coefficients = UPLIFT.from_rgb * color;
+        let [x, y, z] = lambda.map(|lambda| basis(lambda).dot(coefficients).max(0.0));
+
+        Vec3::newf(x, y, z)
+    }
+
+    /// The values at each wavelength of a property given per color channel, like a refractive
+    /// index, following [`interpolate`].
+    pub fn interpolate(self, channels: Rgb) -> Vec3 {
+        let Some(lambda) = self.lambda else {
+            return channels;
+        };
+        let [x, y, z] = lambda.map(|lambda| interpolate(channels, lambda));
+
+        Vec3::newf(x, y, z)
+    }
+
+    /// The linear sRGB color of light with the given values at each wavelength, as an estimate
+    /// that averages to the color of the whole spectrum.
+    pub fn to_rgb(self, values: Vec3) -> Rgb {
+        let Some(lambda) = self.lambda else {
+            return values;
+        };
+        if self.single {
+            return values.x * response(lambda[0]);
+        }
+
+        (values.x * response(lambda[0])
+            + values.y * response(lambda[1])
+            + values.z * response(lambda[2]))
+            / 3.0
+    }
+}
+
+/// How a material's refractive index varies with the wavelength of light, in nanometres.
+#[derive(Clone, Copy)]
+pub enum Dispersion {
+    /// Cauchy's equation, *n* = *a* + *b* / *λ*², with *λ* in micrometres.
+    Cauchy { a: f64, b: f64 },
+    /// The Sellmeier equation, *n*² = 1 + Σ *bᵢ* *λ*² / (*λ*² - *cᵢ*), with *λ* in micrometres.
+    Sellmeier { b: [f64; 3], c: [f64; 3] },
+}
+
+impl Dispersion {
+    /// Borosilicate crown glass, the common optical glass.
+    pub const BK7: Self = Self::Sellmeier {
+        b: [1.039_612_12, 0.231_792_344, 1.010_469_45],
+        c: [0.006_000_698_67, 0.020_017_914_4, 103.560_653],
+    };
+    /// Dense flint glass, which splits colors far more than crown glass.
+    pub const SF11: Self = Self::Sellmeier {
+        b: [1.737_596_95, 0.313_747_346, 1.898_781_01],
+        c: [0.013_188_707, 0.062_306_814_2, 155.236_29],
+    };
+
+    /// The refractive index at `lambda` nanometres.
+    pub fn idx(self, lambda: f64) -> f64 {
+        let micrometres2 = (lambda / 1000.0).powi(2);
+
+        match self {
+            Self::Cauchy { a, b } => a + b / micrometres2,
+            Self::Sellmeier { b, c } => {
+                let terms: f64 = b
+                    .iter()
+                    .zip(c)
+                    .map(|(b, c)| b * micrometres2 / (micrometres2 - c))
+                    .sum();
+                (1.0 + terms).sqrt()
+            }
+        }
+    }
+}
+
+/// The linear sRGB color of light with the spectrum `f` over wavelengths in nanometres, scaled so
+/// that a flat spectrum of 1 is white.
+pub fn integrate(f: impl Fn(f64) -> f64) -> Rgb {
+    // Midpoint rule, ten nanometres at a time.
+    const STEPS: u32 = 40;
+    let step = (LAMBDA_MAX - LAMBDA_MIN) / f64::from(STEPS);
+
+    let (color, white) = (0..STEPS).fold((Rgb::ZERO, Rgb::ZERO), |(color, white), i| {
+        let lambda = (f64::from(i) + 0.5).mul_add(step, LAMBDA_MIN);
+        let response = response(lambda);
+        (response.mul_add(f(lambda), color), white + response)
+    });
+
+    color / white
+}
+
+/// The value at `lambda` nanometres of a smooth spectrum through the values of `channels` at the
+/// red, green and blue ends of the spectrum.
+///
+/// For properties like refractive indices that are given per color channel.
+pub fn interpolate(channels: Rgb, lambda: f64) -> f64 {
+    basis(lambda).dot(channels)
+}
+
+/// The linear sRGB color of the CIE XYZ color `xyz`, which may be out of gamut.
+pub fn xyz_to_rgb(xyz: Vec3) -> Rgb {
+    Rgb::newf(
+        3.2406f64.mul_add(xyz.x, (-1.5372f64).mul_add(xyz.y, -0.4986 * xyz.z)),
+        (-0.9689f64).mul_add(xyz.x, 1.8758f64.mul_add(xyz.y, 0.0415 * xyz.z)),
+        0.0557f64.mul_add(xyz.x, (-0.2040f64).mul_add(xyz.y, 1.0570 * xyz.z)),
+    )
+}
+
+/// The CIE 1931 color matching functions at `lambda` nanometres, from the multi-lobe Gaussian fit
+/// of Wyman, Sloan and Shirley.
+fn cmf(lambda: f64) -> Vec3 {
+    let g = |mu: f64, below: f64, above: f64| {
+        let t = (lambda - mu) / if lambda < mu { below } else { above };
+        (-0.5 * t * t).exp()
+    };
+
+    Vec3::newf(
+        1.056f64.mul_add(
+            g(599.8, 37.9, 31.0),
+            0.362f64.mul_add(g(442.0, 16.0, 26.7), -0.065 * g(501.1, 20.4, 26.2)),
+        ),
+        0.821f64.mul_add(g(568.8, 46.9, 40.5), 0.286 * g(530.9, 16.3, 31.1)),
+        1.217f64.mul_add(g(437.0, 11.8, 36.0), 0.681 * g(459.0, 26.0, 13.8)),
+    )
+}
+
+/// The contribution of `lambda` nanometres to a linear sRGB color, scaled so that a flat spectrum
+/// of 1 averages to white.
+fn response(lambda: f64) -> Rgb {
+    xyz_to_rgb(cmf(lambda)) / UPLIFT.white
+}
+
+/// Three smooth spectra covering the blue, green and red ends of the spectrum, which sum to 1.
+fn basis(lambda: f64) -> Vec3 {
+    let step = |edge: f64| (1.0 + (-(lambda - edge) / 10.0).exp()).recip();
+    let blue = 1.0 - step(490.0);
+    let red = step(590.0);
+
+    Vec3::newf(red, 1.0 - red - blue, blue)
+}
+
+/// Precomputed integrals over the visible spectrum.
+struct Uplift {
+    /// The linear sRGB color of a flat spectrum of 1, before white balancing.
+    white: Rgb,
+    /// The coefficients of the [`basis`] spectra whose sum has a given linear sRGB color.
+    from_rgb: Mat3,
+}
+
+static UPLIFT: LazyLock<Uplift> = LazyLock::new(|| {
+    // Midpoint rule, one nanometre at a time.
+    let wavelengths = (LAMBDA_MIN as u32..LAMBDA_MAX as u32).map(|lambda| f64::from(lambda) + 0.5);
+    let count = LAMBDA_MAX - LAMBDA_MIN;
+
+    let white = wavelengths
+        .clone()
+        .fold(Vec3::ZERO, |sum, lambda| sum + xyz_to_rgb(cmf(lambda)))
+        / count;
+    // The colors of the basis spectra are the columns of the matrix from coefficients to colors.
+    let mut to_rgb = Mat3([[0.0; 3]; 3]);
+    for lambda in wavelengths {
+        let color = xyz_to_rgb(cmf(lambda)) / white / count;
+        let basis = basis(lambda);
+        for (row, channel) in to_rgb.0.iter_mut().zip([color.x, color.y, color.z]) {
+            for (entry, weight) in row.iter_mut().zip([basis.x, basis.y, basis.z]) {
+                *entry = channel.mul_add(weight, *entry);
+            }
+        }
+    }
+
+    Uplift {
+        white,
+        from_rgb: to_rgb.inverse(),
+    }
+});
+
+/// A 3×3 matrix in row-major order.
+#[derive(Clone, Copy)]
+struct Mat3([[f64; 3]; 3]);
+
+impl Mat3 {
+    fn inverse(self) -> Self {
+        let m = self.0;
+        let cofactor = |r: usize, c: usize| {
+            let (r0, r1) = ((r + 1) % 3, (r + 2) % 3);
+            let (c0, c1) = ((c + 1) % 3, (c + 2) % 3);
+            m[r0][c0].mul_add(m[r1][c1], -(m[r0][c1] * m[r1][c0]))
+        };
+        let det = m[0][0].mul_add(
+            cofactor(0, 0),
+            m[0][1].mul_add(cofactor(0, 1), m[0][2] * cofactor(0, 2)),
+        );
+
+        // The transpose of the matrix of cofactors, divided by the determinant.
+        Self([0, 1, 2].map(|r| [0, 1, 2].map(|c| cofactor(c, r) / det)))
+    }
+}
+
+impl std::ops::Mul<Vec3> for Mat3 {
+    type Output = Vec3;
+
+    fn mul(self, v: Vec3) -> Vec3 {
+        let [x, y, z] = self.0.map(|row| Vec3::newf(row[0], row[1], row[2]).dot(v));
+        Vec3::newf(x, y, z)
+    }
+}
@@ -0,0 +1,120 @@
+//! White furnace tests: a non-absorbing object lit evenly from every direction reflects at most
+//! the light it receives, whichever integrator renders it.
+
+use rand::{SeedableRng, rngs::StdRng};
+use sidewinder::{
+    camera::CameraRig,
+    environment::Constant,
+    graphics::{DiffuseLight, HitList, Ray, Solid, Subsurface},
+    integrator::{Bdpt, Integrator, PathTracer},
+    math::{Point, Rgb, Vec3},
+    object::{Quad, Sphere},
+    scene::Scene,
+};
+
+const RADIANCE: f64 = 0.5;
+
+/// A white rough subsurface sphere inside a box whose walls all emit [`RADIANCE`] inwards.
+fn subsurface_furnace() -> Scene {
+    let textures = sidewinder::texlist![
+        "light": Solid::new(Rgb::new_all(RADIANCE)),
+    ];
+    let mats = sidewinder::matlist![
+        "white": Subsurface::new(Rgb::ONE, Rgb::new_all(0.3), 1.45).with_roughness(0.3),
+        "light": DiffuseLight::new(textures["light"].clone()),
+    ];
+    let walls = || {
+        let wall = |q, u, v| Quad::new(q, u, v, mats["light"].clone());
+        sidewinder::hitlist![
+            wall(
+                Point::newi(-2, -2, -2),
+                Vec3::newi(4, 0, 0),
+                Vec3::newi(0, 4, 0)
+            ),
+            wall(
+                Point::newi(-2, -2, 2),
+                Vec3::newi(0, 4, 0),
+                Vec3::newi(4, 0, 0)
+            ),
+            wall(
+                Point::newi(-2, -2, -2),
+                Vec3::newi(0, 4, 0),
+                Vec3::newi(0, 0, 4)
+            ),
+            wall(
+                Point::newi(2, -2, -2),
+                Vec3::newi(0, 0, 4),
+                Vec3::newi(0, 4, 0)
+            ),
+            wall(
+                Point::newi(-2, -2, -2),
+                Vec3::newi(0, 0, 4),
+                Vec3::newi(4, 0, 0)
+            ),
+            wall(
+                Point::newi(-2, 2, -2),
+                Vec3::newi(4, 0, 0),
+                Vec3::newi(0, 0, 4)
+            ),
+        ]
+    };
+
+    let mut world = walls();
+    world.push(Box::new(Sphere::new(
+        Point::newi(0, 0, 0),
+        1.0,
+        mats["white"].clone(),
+    )));
+
+    Scene::new(
+        world,
+        CameraRig::new(
+            Point::newf(0.0, 0.0, 1.8),
+            Point::newi(0, 0, 0),
+            Vec3::newi(0, 1, 0),
+            90.0,
+            0.0,
+            1.8,
+        ),
+    )
+    .with_lights(walls())
+    .with_environment(Constant::new(Rgb::ZERO))
+}
+
+/// The mean brightness of the sphere seen from just inside the box, rendered by `integrator`.
+fn mean_radiance(integrator: &dyn Integrator, scene: &Scene, samples: usize) -> f64 {
+    let mut rng = StdRng::seed_from_u64(1);
+    let origin = Point::newf(0.0, 0.0, 1.8);
+
+    let total: f64 = (0..samples)
+        .map(|i| {
+            // Spread the rays over the visible disk of the sphere.
+            let angle = i as f64 * 2.399_963;
+            let radius = 0.9 * ((i as f64 + 0.5) / samples as f64).sqrt();
+            let target = Point::newf(radius * angle.cos(), radius * angle.sin(), 0.0);
+            let r = Ray::new(origin, target - origin, 0.0);
+            integrator.li(&r, scene, &mut rng).mean()
+        })
+        .sum();
+
+    total / samples as f64
+}
+
+#[test]
+fn bdpt_subsurface_conserves_energy() {
+    const SAMPLES: usize = 20_000;
+
+    let scene = subsurface_furnace();
+    let path = mean_radiance(&PathTracer::new(50, 5, None), &scene, SAMPLES);
+    let bdpt = mean_radiance(&Bdpt::new(50, 5, None), &scene, SAMPLES);
+
+    assert!(path <= RADIANCE + 0.01, "path tracer gives {path}");
+    assert!(
+        bdpt <= RADIANCE + 0.01,
+        "bidirectional path tracer gives {bdpt}"
+    );
+    assert!(
+        (bdpt - path).abs() < 0.015,
+        "bidirectional path tracer gives {bdpt}, path tracer {path}"
+    );
+}